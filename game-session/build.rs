@@ -1,4 +1,3 @@
-use game_session_io::GameSessionMetadata;
 fn main() {
-    gear_wasm_builder::build_with_metadata::<GameSessionMetadata>();
+    gear_wasm_builder::build();
 }
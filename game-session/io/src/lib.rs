@@ -1,32 +1,134 @@
 #![no_std]
 
-use gmeta::{In, InOut, Metadata, Out};
-use gstd::{collections::HashMap, prelude::*, ActorId, MessageId};
+use core::cmp::Ordering;
+use gstd::{
+    collections::{HashMap, VecDeque},
+    prelude::*,
+    ActorId, MessageId,
+};
+use wordle_core::{HintError, WordError};
 
-#[derive(Debug, Default, Clone, Encode, Decode, TypeInfo)]
-pub struct GameSessionState {
-    pub wordle_program_id: ActorId,
-    pub game_sessions: Vec<(ActorId, SessionInfo)>,
+pub use wordle_core::{Alphabet, GameStatus, GuessRecord, LetterFeedback, SessionStatusKind};
+pub use wordle_io::{Action as WordleAction, Event as WordleEvent, GameId, WordleError};
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub enum StateQuery {
+    Session(GameId),
+    /// 玩家的所有会话
+    SessionOf(ActorId),
+    /// 进行中的会话，按游戏 id 排序后分页返回
+    ActiveSessions {
+        offset: u32,
+        limit: u32,
+    },
+    SessionsByStatus(SessionStatusKind),
+    /// 管理员和当前配置
+    Config,
+    Stats(ActorId),
+    /// 排行榜的前 `limit` 名
+    Leaderboard {
+        rank_by: RankBy,
+        limit: u32,
+    },
+    /// 玩家在排行榜中的名次，不符合排名条件时为 `None`
+    PlayerRank {
+        player: ActorId,
+        rank_by: RankBy,
+    },
+    /// 存储中的会话数量和已经压缩的会话的汇总
+    Storage,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub enum StateReply {
+    Session(Option<Box<SessionInfo>>),
+    Sessions(Vec<(GameId, SessionInfo)>),
+    Config {
+        owner: ActorId,
+        config: GameSessionConfig,
+    },
+    Stats(Option<PlayerStats>),
+    Leaderboard(Vec<LeaderboardEntry>),
+    PlayerRank(Option<LeaderboardEntry>),
+    Storage {
+        /// 存储中的会话，包括已经结束或者没能开始、但还在保留期内的
+        live_sessions: u32,
+        archived: ArchiveSummary,
+    },
 }
 
-#[derive(Debug, Default, Clone, Encode, Decode, TypeInfo)]
-pub struct GameSessionInit {
+/// 初始化参数就是游戏会话的初始配置
+pub type GameSessionInit = GameSessionConfig;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct GameSessionConfig {
     pub wordle_program_id: ActorId,
+    /// 每局游戏的尝试次数上限
+    pub tries_limit: u8,
+    /// 游戏开始后经过多少个区块超时
+    pub timeout_blocks: u32,
+    /// 两次猜测之间最多间隔多少个区块，`None` 表示不限制
+    pub guess_timeout_blocks: Option<u32>,
+    /// 超时前多少个区块发送 `TimeoutWarning`，0 表示不发送
+    pub warning_blocks: u32,
+    /// 超时检查消息 CheckGameStatus 的 gas
+    pub check_gas: u64,
+    /// 等待 Wordle 回复时为 handle_signal 预留的系统 gas
+    pub signal_gas: u64,
+    /// 等待 Wordle 回复的最大区块数，超过后放弃这次请求
+    pub reply_timeout_blocks: u32,
+    /// 默认的单词长度（字母数，不是字节数）。每日挑战的单词长度由 Wordle 程序的
+    /// `daily_word_length` 决定，以 Wordle 开始游戏时的回复为准
+    pub word_length: u8,
+    /// 每个玩家同时进行的游戏数量上限
+    pub max_games_per_player: u32,
+    /// 游戏结束后会话在存储中保留多少个区块，之后被压缩进 `ArchiveSummary`
+    pub retention_blocks: u32,
 }
 
-impl GameSessionInit {
+impl GameSessionConfig {
     pub fn assert_valid(&self) {
-        assert!(
-            !self.wordle_program_id.is_zero(),
-            "Invalid wordle_program_id"
-        );
+        if let Err(reason) = self.validate() {
+            panic!("{reason}");
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.wordle_program_id.is_zero() {
+            return Err("Invalid wordle_program_id");
+        }
+        if self.tries_limit == 0 {
+            return Err("Invalid tries_limit");
+        }
+        if self.timeout_blocks == 0 {
+            return Err("Invalid timeout_blocks");
+        }
+        if self.guess_timeout_blocks == Some(0) {
+            return Err("Invalid guess_timeout_blocks");
+        }
+        if self.check_gas == 0 {
+            return Err("Invalid check_gas");
+        }
+        if self.signal_gas == 0 {
+            return Err("Invalid signal_gas");
+        }
+        if self.reply_timeout_blocks == 0 {
+            return Err("Invalid reply_timeout_blocks");
+        }
+        if !wordle_core::is_supported_length(self.word_length) {
+            return Err("Invalid word_length");
+        }
+        if self.max_games_per_player == 0 {
+            return Err("Invalid max_games_per_player");
+        }
+        Ok(())
     }
 }
 
 impl From<GameSessionInit> for GameSession {
     fn from(game_session_init: GameSessionInit) -> Self {
         Self {
-            wordle_program_id: game_session_init.wordle_program_id,
+            config: game_session_init,
             ..Default::default()
         }
     }
@@ -34,139 +136,545 @@ impl From<GameSessionInit> for GameSession {
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo)]
 pub enum GameSessionAction {
-    StartGame,
+    /// 开始一局新游戏，游戏 id 在 `StartSuccess` 中返回。
+    /// 困难模式下每次猜测都必须使用之前得到的所有提示，
+    /// `word_length` 为 `None` 时使用配置中的单词长度
+    StartGame {
+        hard_mode: bool,
+        word_length: Option<u8>,
+    },
+    /// 开始本周期的每日挑战，所有玩家猜同一个单词，每个周期只能玩一次。
+    /// 单词长度为 Wordle 程序的 `daily_word_length`，由 Wordle 在 `GameStarted` 中告知，
+    /// 保存在 `SessionInfo::word_length`
+    StartDailyGame {
+        hard_mode: bool,
+    },
     CheckWord {
+        game_id: GameId,
         word: String,
     },
     CheckGameStatus {
-        user: ActorId,
-        session_id: MessageId,
+        game_id: GameId,
+    },
+    /// 只有管理员可以修改配置，新配置只对之后开始的游戏生效
+    UpdateConfig(GameSessionConfig),
+    TransferOwnership {
+        new_owner: ActorId,
+    },
+    /// 与 `CheckWord` 相同，但以旧格式的 `CheckWordResultV1` 回复
+    CheckWordV1 {
+        game_id: GameId,
+        word: String,
     },
-}
-
-#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
-pub enum WordleAction {
-    StartGame { user: ActorId },
-    CheckWord { user: ActorId, word: String },
 }
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo)]
 pub enum GameSessionEvent {
-    StartSuccess,
+    StartSuccess {
+        game_id: GameId,
+    },
+    /// `feedback` 中每个字母对应一项
     CheckWordResult {
+        game_id: GameId,
+        feedback: Vec<LetterFeedback>,
+    },
+    /// `guesses` 是这局游戏所有计分的猜测，可以用来还原棋盘
+    GameOver {
+        game_id: GameId,
+        status: GameStatus,
+        guesses: Vec<GuessRecord>,
+    },
+    Error(GameSessionError),
+    /// 游戏结束后公开的秘密单词，可以用 `SessionInfo::commitment` 验证
+    WordRevealed {
+        game_id: GameId,
+        word: String,
+        salt: [u8; 32],
+    },
+    ConfigUpdated(GameSessionConfig),
+    OwnershipTransferred {
+        previous_owner: ActorId,
+        new_owner: ActorId,
+    },
+    /// Wordle 没有按时回复或者处理消息时出错，玩家可以重新发送刚才的请求。
+    /// 放弃的是开始游戏的请求时，`game_id` 这局游戏不会开始
+    WordleUnavailable {
+        game_id: GameId,
+    },
+    /// 再过 `blocks_left` 个区块游戏就会超时
+    TimeoutWarning {
+        game_id: GameId,
+        blocks_left: u32,
+    },
+    /// 每日挑战结束了，但单词要到 `reveal_at` 区块（周期结束）才在 Wordle 的状态中公开
+    WordHidden {
+        game_id: GameId,
+        reveal_at: u32,
+    },
+    /// `CheckWordV1` 的回复：位置正确的字母和在单词中但位置不对的字母的下标
+    CheckWordResultV1 {
+        game_id: GameId,
         correct_positions: Vec<u8>,
         contained_in_word: Vec<u8>,
     },
-    GameOver(GameStatus),
 }
 
-#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
-pub enum GameStatus {
-    Win,
-    Lose,
+impl GameSessionEvent {
+    /// 把 `CheckWordResult` 换成旧格式的 `CheckWordResultV1`，其他事件不变
+    pub fn into_v1(self) -> Self {
+        match self {
+            GameSessionEvent::CheckWordResult { game_id, feedback } => {
+                let (correct_positions, contained_in_word) =
+                    wordle_core::feedback_positions(&feedback);
+                GameSessionEvent::CheckWordResultV1 {
+                    game_id,
+                    correct_positions,
+                    contained_in_word,
+                }
+            }
+            event => event,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
-pub enum WordleEvent {
-    GameStarted {
-        user: ActorId,
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum GameSessionError {
+    NotInGame,
+    InvalidWordLength,
+    InvalidCharacters,
+    /// 单词不在 Wordle 的词典中，不计入尝试次数
+    NotInDictionary,
+    /// Wordle 程序返回了错误
+    Wordle(WordleError),
+    NotOwner,
+    InvalidConfig,
+    /// 上一次请求还在等待 Wordle 的回复
+    RequestPending,
+    /// 玩家同时进行的游戏已经达到上限
+    TooManyGames,
+    /// 困难模式：`position` 位置必须使用已经确定的字母 `letter`
+    MissingCorrectLetter {
+        position: u8,
+        letter: String,
     },
-    WordChecked {
-        user: ActorId,
-        correct_positions: Vec<u8>,
-        contained_in_word: Vec<u8>,
+    /// 困难模式：猜测必须包含已知在单词中的字母 `letter`
+    MissingPresentLetter {
+        letter: String,
     },
 }
 
-impl WordleEvent {
-    pub fn get_user(&self) -> &ActorId {
+impl From<WordError> for GameSessionError {
+    fn from(error: WordError) -> Self {
+        match error {
+            WordError::InvalidWordLength => GameSessionError::InvalidWordLength,
+            WordError::InvalidCharacters => GameSessionError::InvalidCharacters,
+        }
+    }
+}
+
+impl From<HintError> for GameSessionError {
+    fn from(error: HintError) -> Self {
+        match error {
+            HintError::MissingCorrectLetter { position, letter } => {
+                GameSessionError::MissingCorrectLetter { position, letter }
+            }
+            HintError::MissingPresentLetter { letter } => {
+                GameSessionError::MissingPresentLetter { letter }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub enum RankBy {
+    Wins,
+    /// 胜率，只有玩过至少 `min_games` 局的玩家参与排名
+    WinRate {
+        min_games: u32,
+    },
+    /// 胜局的平均猜测次数，越少越好，只有赢过的玩家参与排名
+    AverageGuesses,
+}
+
+impl RankBy {
+    fn is_eligible(&self, stats: &PlayerStats) -> bool {
         match self {
-            WordleEvent::GameStarted { user } => user,
-            WordleEvent::WordChecked { user, .. } => user,
+            RankBy::Wins => stats.games_played > 0,
+            RankBy::WinRate { min_games } => {
+                stats.games_played > 0 && stats.games_played >= *min_games
+            }
+            RankBy::AverageGuesses => stats.wins > 0,
         }
     }
 
-    pub fn has_guessed(&self) -> bool {
+    // 返回 `Ordering::Less` 表示 `a` 排在 `b` 前面
+    fn compare(&self, a: &LeaderboardEntry, b: &LeaderboardEntry) -> Ordering {
         match self {
-            WordleEvent::GameStarted { .. } => unimplemented!(),
-            WordleEvent::WordChecked {
-                correct_positions, ..
-            } => correct_positions == &vec![0, 1, 2, 3, 4],
+            RankBy::Wins => b
+                .wins
+                .cmp(&a.wins)
+                .then(a.games_played.cmp(&b.games_played)),
+            RankBy::WinRate { .. } => {
+                // 交叉相乘比较 wins / games_played，避免浮点数
+                let a_rate = a.wins as u64 * b.games_played as u64;
+                let b_rate = b.wins as u64 * a.games_played as u64;
+                b_rate.cmp(&a_rate).then(b.wins.cmp(&a.wins))
+            }
+            RankBy::AverageGuesses => {
+                // 交叉相乘比较 total_guesses / wins，避免浮点数
+                let a_average = a.total_guesses as u64 * b.wins as u64;
+                let b_average = b.total_guesses as u64 * a.wins as u64;
+                a_average.cmp(&b_average).then(b.wins.cmp(&a.wins))
+            }
         }
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct LeaderboardEntry {
+    pub rank: u32,
+    pub player: ActorId,
+    pub games_played: u32,
+    pub wins: u32,
+    /// 胜局中猜测次数的总和，平均猜测次数为 `total_guesses / wins`
+    pub total_guesses: u32,
+}
+
+/// 玩家的历史战绩，每局游戏结束时更新
+#[derive(Debug, Default, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct PlayerStats {
+    pub games_played: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub timeouts: u32,
+    pub current_streak: u32,
+    pub max_streak: u32,
+    /// 第 i 项是用 i + 1 次猜中的胜局数
+    pub guess_distribution: Vec<u32>,
+}
+
+impl PlayerStats {
+    pub fn record_win(&mut self, tries: u8, tries_limit: u8) {
+        self.games_played += 1;
+        self.wins += 1;
+        self.current_streak += 1;
+        self.max_streak = self.max_streak.max(self.current_streak);
+
+        let len = self
+            .guess_distribution
+            .len()
+            .max(tries_limit.max(tries) as usize);
+        self.guess_distribution.resize(len, 0);
+        if tries > 0 {
+            self.guess_distribution[tries as usize - 1] += 1;
+        }
+    }
+
+    /// 所有胜局中猜测次数的总和
+    pub fn total_guesses(&self) -> u32 {
+        self.guess_distribution
+            .iter()
+            .enumerate()
+            .map(|(i, count)| (i as u32 + 1) * count)
+            .sum()
+    }
+
+    pub fn record_loss(&mut self) {
+        self.games_played += 1;
+        self.losses += 1;
+        self.current_streak = 0;
+    }
+
+    pub fn record_timeout(&mut self) {
+        self.games_played += 1;
+        self.timeouts += 1;
+        self.current_streak = 0;
+    }
+}
+
 impl From<&WordleEvent> for GameSessionEvent {
     fn from(wordle_event: &WordleEvent) -> Self {
         match wordle_event {
-            WordleEvent::GameStarted { .. } => GameSessionEvent::StartSuccess,
+            WordleEvent::GameStarted { game_id, .. } => {
+                GameSessionEvent::StartSuccess { game_id: *game_id }
+            }
             WordleEvent::WordChecked {
+                game_id, feedback, ..
+            } => GameSessionEvent::CheckWordResult {
+                game_id: *game_id,
+                feedback: feedback.clone(),
+            },
+            WordleEvent::InvalidWord { .. } => {
+                GameSessionEvent::Error(GameSessionError::NotInDictionary)
+            }
+            WordleEvent::GameOver {
+                game_id,
+                word,
+                salt,
+                ..
+            } => GameSessionEvent::WordRevealed {
+                game_id: *game_id,
+                word: word.clone(),
+                salt: *salt,
+            },
+            WordleEvent::Error(error) => {
+                GameSessionEvent::Error(GameSessionError::Wordle(error.clone()))
+            }
+            WordleEvent::GameEnded {
+                game_id, reveal_at, ..
+            } => GameSessionEvent::WordHidden {
+                game_id: *game_id,
+                reveal_at: *reveal_at,
+            },
+            WordleEvent::WordCheckedV1 {
+                game_id,
                 correct_positions,
                 contained_in_word,
                 ..
-            } => GameSessionEvent::CheckWordResult {
+            } => GameSessionEvent::CheckWordResultV1 {
+                game_id: *game_id,
                 correct_positions: correct_positions.clone(),
                 contained_in_word: contained_in_word.clone(),
             },
+            // GameSession 不发送管理消息，Wordle 不会用这些事件回复
+            WordleEvent::WordsAdded { .. }
+            | WordleEvent::WordsRemoved { .. }
+            | WordleEvent::DictionaryExtended { .. }
+            | WordleEvent::CallerAuthorized(..)
+            | WordleEvent::CallerRevoked(..) => unreachable!("Wordle 回复了管理消息的事件"),
         }
     }
 }
 
-#[derive(Default, Debug, Clone, Encode, Decode, TypeInfo)]
-pub enum SessionStatus {
-    #[default]
-    Init,
-    WaitUserInput,
-    WaitWordleStartReply,
-    WaitWordleCheckWordReply,
-    ReplyReceived(WordleEvent),
-    GameOver(GameStatus),
-}
+/// 会话状态及其转换由 wordle-core 定义，等待中收到的回复是 Wordle 的事件
+pub type SessionStatus = wordle_core::SessionStatus<WordleEvent>;
 
 #[derive(Default, Debug, Clone, Encode, Decode, TypeInfo)]
 pub struct SessionInfo {
-    pub session_id: MessageId,
+    /// 玩家
+    pub user: ActorId,
+    /// 是否为每日挑战
+    pub daily: bool,
+    /// 是否为困难模式
+    pub hard_mode: bool,
+    /// 这局游戏的单词长度，每次猜测都按这个长度检查。游戏开始后是 Wordle 回复的长度
+    pub word_length: u8,
+    /// 这局游戏的单词使用的字母表，由 Wordle 在开始游戏时告知，每次猜测都按它检查
+    pub alphabet: Alphabet,
+    /// 已经计分的猜测，按猜测的顺序排列
+    pub guesses: Vec<GuessRecord>,
     pub original_msg_id: MessageId,
     pub send_to_wordle_msg_id: MessageId,
     pub tries: u8,
     pub session_status: SessionStatus,
+    /// Wordle 在游戏开始时公布的秘密单词承诺
+    pub commitment: [u8; 32],
+    /// 游戏开始时的配置，之后修改配置不影响进行中的游戏
+    pub config: GameSessionConfig,
+    /// 整局游戏的截止区块
+    pub game_deadline: u32,
+    /// 本次猜测的截止区块，每次猜测后重新计算
+    pub guess_deadline: Option<u32>,
+    /// 已经发送过 `TimeoutWarning` 的截止区块，避免重复警告
+    pub warned_deadline: Option<u32>,
 }
 
 impl SessionInfo {
-    pub fn is_wait_reply_status(&self) -> bool {
-        matches!(
-            self.session_status,
-            SessionStatus::WaitWordleCheckWordReply | SessionStatus::WaitWordleStartReply
-        )
+    /// 困难模式下检查 `word` 是否使用了之前所有猜测得到的提示
+    pub fn check_hard_mode(&self, word: &str) -> Result<(), GameSessionError> {
+        if !self.hard_mode {
+            return Ok(());
+        }
+        wordle_core::check_hard_mode(&self.guesses, word).map_err(Into::into)
+    }
+
+    /// Wordle 是否已经开始这局游戏，游戏开始时才计算整局游戏的截止区块
+    pub fn is_started(&self) -> bool {
+        self.game_deadline != 0
+    }
+
+    /// 最近的截止区块，即整局游戏和本次猜测的截止区块中较早的一个
+    pub fn deadline(&self) -> u32 {
+        self.guess_deadline
+            .map_or(self.game_deadline, |guess_deadline| {
+                guess_deadline.min(self.game_deadline)
+            })
+    }
+
+    /// 到达 [`Self::deadline`] 时游戏以哪种超时结束，两个截止区块相同时算作整局游戏超时
+    pub fn timeout_status(&self) -> GameStatus {
+        match self.guess_deadline {
+            Some(guess_deadline) if guess_deadline < self.game_deadline => GameStatus::GuessTimeout,
+            _ => GameStatus::GameTimeout,
+        }
+    }
+}
+
+/// 已经压缩的会话的汇总，玩家的战绩在游戏结束时已经记录在 `PlayerStats` 中
+#[derive(Debug, Default, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct ArchiveSummary {
+    pub sessions: u64,
+    pub wins: u64,
+    pub losses: u64,
+    pub timeouts: u64,
+    /// 没有开始的游戏：Wordle 没有回复或者拒绝了开始游戏的请求
+    pub abandoned: u64,
+    /// 所有压缩的会话中计分的猜测次数
+    pub total_guesses: u64,
+}
+
+impl ArchiveSummary {
+    fn record(&mut self, session_info: &SessionInfo) {
+        self.sessions += 1;
+        match session_info.session_status {
+            SessionStatus::GameOver(GameStatus::Win) => self.wins += 1,
+            SessionStatus::GameOver(GameStatus::Lose) => self.losses += 1,
+            SessionStatus::GameOver(GameStatus::GameTimeout | GameStatus::GuessTimeout) => {
+                self.timeouts += 1
+            }
+            SessionStatus::Init => self.abandoned += 1,
+            _ => {}
+        }
+        self.total_guesses += session_info.guesses.len() as u64;
     }
 }
 
 #[derive(Default, Debug, Clone)]
 pub struct GameSession {
-    pub wordle_program_id: ActorId,
-    pub sessions: HashMap<ActorId, SessionInfo>,
+    pub owner: ActorId,
+    pub config: GameSessionConfig,
+    /// 下一局游戏的 id
+    pub next_game_id: u64,
+    pub sessions: HashMap<GameId, SessionInfo>,
+    /// 正在等待 Wordle 回复的消息和对应的游戏
+    pub waiting: HashMap<MessageId, GameId>,
+    /// 同步版本发给 Wordle 的请求和对应的游戏，Wordle 的回复据此找到会话
+    pub requests: HashMap<MessageId, GameId>,
+    /// 发给 Wordle 的 EndGame 消息和接收它的 Wordle 程序，只转发这些消息的回复中公开的秘密单词
+    pub ending: HashMap<MessageId, ActorId>,
+    pub stats: HashMap<ActorId, PlayerStats>,
+    /// 每个玩家正在进行的游戏数量，没有进行中的游戏的玩家不在其中
+    pub active_games: HashMap<ActorId, u32>,
+    /// 已经结束的游戏和结束时的区块，按结束的顺序排列
+    pub finished: VecDeque<(u32, GameId)>,
+    pub archived: ArchiveSummary,
 }
 
-impl From<&GameSession> for GameSessionState {
-    fn from(game_session: &GameSession) -> Self {
-        Self {
-            wordle_program_id: game_session.wordle_program_id,
-            game_sessions: game_session
-                .sessions
-                .iter()
-                .map(|(k, v)| (*k, v.clone()))
-                .collect(),
+impl GameSession {
+    pub fn issue_game_id(&mut self) -> GameId {
+        self.next_game_id += 1;
+        GameId(self.next_game_id)
+    }
+
+    /// 保存新开始的会话，计入玩家正在进行的游戏
+    pub fn start_session(&mut self, game_id: GameId, session_info: SessionInfo) {
+        *self.active_games.entry(session_info.user).or_default() += 1;
+        self.sessions.insert(game_id, session_info);
+    }
+
+    /// 记录游戏在 `block` 区块结束，保留期过后由 `compact_sessions` 压缩
+    pub fn finish_session(&mut self, game_id: GameId, block: u32) {
+        self.release_game(game_id);
+        self.finished.push_back((block, game_id));
+    }
+
+    /// 立即把 `game_id` 的会话从存储中删除，计入 `archived`，用于没有开始的游戏
+    pub fn archive_session(&mut self, game_id: GameId) {
+        self.release_game(game_id);
+        if let Some(session_info) = self.sessions.remove(&game_id) {
+            self.archived.record(&session_info);
+        }
+    }
+
+    // 游戏结束或者没有开始，不再计入玩家正在进行的游戏
+    fn release_game(&mut self, game_id: GameId) {
+        let Some(user) = self
+            .sessions
+            .get(&game_id)
+            .map(|session_info| session_info.user)
+        else {
+            return;
+        };
+        if let Some(count) = self.active_games.get_mut(&user) {
+            *count -= 1;
+            if *count == 0 {
+                self.active_games.remove(&user);
+            }
         }
     }
-}
 
-pub struct GameSessionMetadata;
+    /// 在 `block` 区块放弃 `game_id` 等待的 Wordle 回复。放弃的是开始游戏的请求时
+    /// 游戏没有开始，会话和结束的游戏一样在保留期过后压缩
+    pub fn cancel_wordle_request(&mut self, game_id: GameId, block: u32) {
+        let Some(session_info) = self.sessions.get_mut(&game_id) else {
+            return;
+        };
+        self.requests.remove(&session_info.send_to_wordle_msg_id);
+        let started = session_info.is_started();
+        session_info.session_status.cancel_wordle_request(started);
+        if matches!(session_info.session_status, SessionStatus::Init) {
+            self.finish_session(game_id, block);
+        }
+    }
 
-impl Metadata for GameSessionMetadata {
-    type Init = In<GameSessionInit>;
-    type Handle = InOut<GameSessionAction, GameSessionEvent>;
-    type Reply = ();
-    type Others = ();
-    type Signal = ();
-    type State = Out<GameSessionState>;
+    /// 把在 `now` 区块时已经超过保留期的会话从存储中删除，计入 `archived`
+    pub fn compact_sessions(&mut self, now: u32) {
+        let retention_blocks = self.config.retention_blocks;
+        while let Some(&(block, game_id)) = self.finished.front() {
+            if block.saturating_add(retention_blocks) > now {
+                break;
+            }
+            self.finished.pop_front();
+            if let Some(session_info) = self.sessions.remove(&game_id) {
+                self.archived.record(&session_info);
+            }
+        }
+    }
+
+    /// 玩家正在进行的游戏数量
+    pub fn active_games_of(&self, user: &ActorId) -> u32 {
+        self.active_games.get(user).copied().unwrap_or_default()
+    }
+
+    /// 按 `rank_by` 排名的完整排行榜，名次从 1 开始
+    pub fn leaderboard(&self, rank_by: &RankBy) -> Vec<LeaderboardEntry> {
+        let mut entries: Vec<LeaderboardEntry> = self
+            .stats
+            .iter()
+            .filter(|(_, stats)| rank_by.is_eligible(stats))
+            .map(|(player, stats)| LeaderboardEntry {
+                rank: 0,
+                player: *player,
+                games_played: stats.games_played,
+                wins: stats.wins,
+                total_guesses: stats.total_guesses(),
+            })
+            .collect();
+        entries.sort_by(|a, b| rank_by.compare(a, b).then(a.player.cmp(&b.player)));
+        for (i, entry) in entries.iter_mut().enumerate() {
+            entry.rank = i as u32 + 1;
+        }
+        entries
+    }
+
+    /// 满足 `filter` 的会话，按游戏 id 排序以便分页结果稳定，只克隆返回的那一页
+    pub fn sessions_page(
+        &self,
+        filter: impl Fn(&SessionInfo) -> bool,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<(GameId, SessionInfo)> {
+        let mut sessions: Vec<(&GameId, &SessionInfo)> = self
+            .sessions
+            .iter()
+            .filter(|(_, session_info)| filter(session_info))
+            .collect();
+        sessions.sort_by_key(|(game_id, _)| **game_id);
+        sessions
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .map(|(game_id, session_info)| (*game_id, session_info.clone()))
+            .collect()
+    }
 }
@@ -0,0 +1,174 @@
+use super::*;
+use gstd::msg::CodecMessageFuture;
+
+// 每个请求都在同一条消息里等待 Wordle 的回复，不需要 ReplyReceived 状态和手动唤醒
+#[gstd::async_main(handle_reply = handle_wordle_reply, handle_signal = cancel_waiting_session)]
+async fn main() {
+    // 解码并处理游戏会话动作
+    let game_session_action: GameSessionAction = msg::load().expect("无法解码 GameSessionAction");
+    let v1 = matches!(game_session_action, GameSessionAction::CheckWordV1 { .. });
+    let game_session = get_game_session_mut();
+    match game_session_action {
+        action @ (GameSessionAction::StartGame { hard_mode, .. }
+        | GameSessionAction::StartDailyGame { hard_mode }) => {
+            // 开始新游戏前压缩超过保留期的会话，由开始游戏的玩家支付 gas
+            game_session.compact_sessions(exec::block_height());
+            let user = msg::source(); // 获取消息发送者，即玩家
+            if game_session.active_games_of(&user) >= game_session.config.max_games_per_player {
+                msg::reply(GameSessionEvent::Error(GameSessionError::TooManyGames), 0)
+                    .expect("回复消息失败");
+                return;
+            }
+            let word_length = match requested_word_length(&action, &game_session.config) {
+                Ok(word_length) => word_length,
+                Err(error) => {
+                    msg::reply(GameSessionEvent::Error(error), 0).expect("回复消息失败");
+                    return;
+                }
+            };
+            let game_id = game_session.issue_game_id();
+            // 记录当前配置，之后修改配置不影响这局游戏
+            let session_info = SessionInfo {
+                user,
+                daily: matches!(action, GameSessionAction::StartDailyGame { .. }),
+                hard_mode,
+                word_length,
+                config: game_session.config.clone(),
+                session_status: SessionStatus::WaitWordleStartReply,
+                ..Default::default()
+            };
+            let start_action = start_wordle_game(game_id, &session_info);
+            game_session.start_session(game_id, session_info);
+
+            let reply = send_to_wordle(game_id, start_action).await;
+            if let Some((game_id, _)) = waiting_session() {
+                let game_session = get_game_session_mut();
+                match reply {
+                    Ok(wordle_event) => apply_start_reply(game_session, game_id, &wordle_event),
+                    Err(_) => reply_wordle_unavailable(game_session, game_id),
+                }
+            }
+        }
+        GameSessionAction::CheckWord { game_id, word }
+        | GameSessionAction::CheckWordV1 { game_id, word } => {
+            let user = msg::source();
+            // 只能在自己的游戏中猜测
+            let Some(session_info) = game_session
+                .sessions
+                .get_mut(&game_id)
+                .filter(|session_info| session_info.user == user)
+            else {
+                msg::reply(GameSessionEvent::Error(GameSessionError::NotInGame), 0)
+                    .expect("回复消息失败");
+                return;
+            };
+            match session_info.session_status {
+                SessionStatus::WaitUserInput => {}
+                SessionStatus::WaitWordleCheckWordReply => {
+                    msg::reply(GameSessionEvent::Error(GameSessionError::RequestPending), 0)
+                        .expect("回复消息失败");
+                    return;
+                }
+                _ => {
+                    msg::reply(GameSessionEvent::Error(GameSessionError::NotInGame), 0)
+                        .expect("回复消息失败");
+                    return;
+                }
+            }
+            if let Err(error) =
+                check_word(&word, session_info).and_then(|()| session_info.check_hard_mode(&word))
+            {
+                msg::reply(GameSessionEvent::Error(error), 0).expect("回复消息失败");
+                return;
+            }
+            session_info.session_status = SessionStatus::WaitWordleCheckWordReply;
+
+            let reply = send_to_wordle(
+                game_id,
+                WordleAction::CheckWord {
+                    user,
+                    game_id,
+                    word: word.clone(),
+                },
+            )
+            .await;
+            if let Some((game_id, _)) = waiting_session() {
+                match reply {
+                    Ok(wordle_event) => apply_check_word_reply(
+                        get_game_session_mut(),
+                        game_id,
+                        word,
+                        &wordle_event,
+                        v1,
+                    ),
+                    Err(_) => reply_wordle_unavailable(get_game_session_mut(), game_id),
+                }
+            }
+        }
+        GameSessionAction::CheckGameStatus { game_id } => {
+            check_game_status(game_session, game_id);
+        }
+        GameSessionAction::UpdateConfig(config) => update_config(game_session, config),
+        GameSessionAction::TransferOwnership { new_owner } => {
+            transfer_ownership(game_session, new_owner);
+        }
+    }
+}
+
+// 向 Wordle 发送请求，最多等待 reply_timeout_blocks 个区块
+fn send_to_wordle(game_id: GameId, action: WordleAction) -> CodecMessageFuture<WordleEvent> {
+    let game_session = get_game_session_mut();
+    let session_info = game_session.sessions.get_mut(&game_id).expect("会话不存在");
+    let future = msg::send_for_reply_as(session_info.config.wordle_program_id, action, 0, 0)
+        .expect("发送消息失败")
+        .up_to(Some(session_info.config.reply_timeout_blocks))
+        .expect("设置等待时间失败");
+    session_info.original_msg_id = msg::id();
+    session_info.send_to_wordle_msg_id = future.waiting_reply_to;
+    game_session.waiting.insert(msg::id(), game_id);
+    future
+}
+
+// 等待期间会话可能已经超时结束，只处理仍在等待这条消息的会话
+fn waiting_session() -> Option<(GameId, &'static mut SessionInfo)> {
+    let game_session = get_game_session_mut();
+    let game_id = game_session.waiting.remove(&msg::id())?;
+    game_session
+        .sessions
+        .get_mut(&game_id)
+        .filter(|session_info| session_info.session_status.is_wait_reply_status())
+        .map(|session_info| (game_id, session_info))
+}
+
+// gstd 把回复交给等待它的消息之后调用，那条消息要在之后才继续执行
+fn handle_wordle_reply() {
+    let game_session = get_game_session_mut();
+    if forward_revealed_word(
+        game_session,
+        msg::reply_to().expect("查询 reply_to 数据失败"),
+    ) {
+        return;
+    }
+    if !msg::reply_code().expect("查询回复码失败").is_success() {
+        return;
+    }
+    if let Ok(WordleEvent::GameStarted { user, game_id, .. }) = msg::load::<WordleEvent>() {
+        let waiting = game_session
+            .sessions
+            .get(&game_id)
+            .is_some_and(|session_info| {
+                matches!(
+                    session_info.session_status,
+                    SessionStatus::WaitWordleStartReply
+                )
+            });
+        if !waiting {
+            end_late_started_game(game_session, user, game_id);
+        }
+    }
+}
+
+// gstd 在每次等待回复时为 handle_signal 预留系统 gas，数量取自配置
+pub(crate) fn reserve_signal_gas(config: &GameSessionConfig) {
+    gstd::Config::set_system_reserve(config.signal_gas).expect("设置系统 gas 预留失败");
+}
@@ -1,9 +1,11 @@
 #![no_std]
 use game_session_io::*;
-use gstd::{exec, msg};
+use gstd::{exec, msg, prelude::*, ActorId, MessageId};
+use wordle_core::GuessOutcome;
 
-// 尝试次数的上限
-const TRIES_LIMIT: u8 = 5;
+// 启用 async 特性时用 gstd 的 async 运行时处理消息，代替下面的 wait/wake 流程
+#[cfg(feature = "async")]
+mod async_handle;
 
 // 全局静态变量，用于存储游戏会话状态
 static mut GAME_SESSION_STATE: Option<GameSession> = None;
@@ -12,147 +14,625 @@ static mut GAME_SESSION_STATE: Option<GameSession> = None;
 extern "C" fn init() {
     let game_session_init: GameSessionInit = msg::load().expect("无法解码 GameSessionInit");
     game_session_init.assert_valid(); // 验证数据有效性
+    #[cfg(feature = "async")]
+    async_handle::reserve_signal_gas(&game_session_init);
 
+    // 初始化游戏会话状态，部署者成为管理员
+    let mut game_session: GameSession = game_session_init.into();
+    game_session.owner = msg::source();
     unsafe {
-        // 初始化游戏会话状态
-        GAME_SESSION_STATE = Some(game_session_init.into());
+        GAME_SESSION_STATE = Some(game_session);
     };
 }
 
+#[cfg(not(feature = "async"))]
 #[no_mangle]
 extern "C" fn handle() {
     // 解码并处理游戏会话动作
     let game_session_action: GameSessionAction = msg::load().expect("无法解码 GameSessionAction");
+    let v1 = matches!(game_session_action, GameSessionAction::CheckWordV1 { .. });
     let game_session = get_game_session_mut();
     match game_session_action {
-        GameSessionAction::StartGame => {
-            let user = msg::source(); // 获取消息发送者，即玩家
-            let session_info = game_session.sessions.entry(user).or_default();
-            match &session_info.session_status {
-                SessionStatus::ReplyReceived(wordle_event) => {
-                    // 如果之前收到过回复，则回复玩家游戏已启动
-                    msg::reply::<GameSessionEvent>(wordle_event.into(), 0).expect("回复消息失败");
-                    session_info.session_status = SessionStatus::WaitUserInput; // 更新状态为等待玩家输入
+        action @ (GameSessionAction::StartGame { hard_mode, .. }
+        | GameSessionAction::StartDailyGame { hard_mode }) => {
+            if let Some(game_id) = game_session.waiting.remove(&msg::id()) {
+                // 等待 Wordle 回复的消息被唤醒
+                // 等待期间游戏可能已经超时结束并被压缩
+                let Some(session_info) = game_session.sessions.get_mut(&game_id) else {
+                    return;
+                };
+                match &session_info.session_status {
+                    SessionStatus::ReplyReceived(wordle_event) => {
+                        // 如果之前收到过回复，则回复玩家游戏已启动
+                        let wordle_event = wordle_event.clone();
+                        apply_start_reply(game_session, game_id, &wordle_event);
+                    }
+                    SessionStatus::WaitWordleStartReply => {
+                        // 等待超时或 Wordle 出错后被唤醒，玩家可以重新开始游戏
+                        reply_wordle_unavailable(game_session, game_id);
+                    }
+                    // 等待期间游戏已经超时结束，玩家已经收到通知
+                    _ => {}
                 }
-                SessionStatus::Init
-                | SessionStatus::GameOver(..)
-                | SessionStatus::WaitWordleStartReply => {
-                    // 向Wordle程序发送"StartGame"消息
-                    let send_to_wordle_msg_id = msg::send(
-                        game_session.wordle_program_id,
-                        WordleAction::StartGame { user },
-                        0,
-                    )
-                    .expect("发送消息失败");
-                    session_info.session_id = msg::id(); // 保存当前消息ID
-                    session_info.original_msg_id = msg::id(); // 保存初始消息ID
-                    session_info.send_to_wordle_msg_id = send_to_wordle_msg_id; // 保存发送到Wordle的消息ID
-                    session_info.tries = 0; // 初始化尝试次数
-                    session_info.session_status = SessionStatus::WaitWordleStartReply; // 更新状态为等待Wordle启动回复
-
-                    msg::send_delayed(
-                        exec::program_id(),
-                        GameSessionAction::CheckGameStatus {
-                            user,
-                            session_id: msg::id(),
-                        },
-                        1000000,
-                        200,
-                    )
-                    .expect("发送延迟消息失败");
-                    exec::wait(); // 等待回复
+                return;
+            }
+            // 开始新游戏前压缩超过保留期的会话，由开始游戏的玩家支付 gas
+            game_session.compact_sessions(exec::block_height());
+            let user = msg::source(); // 获取消息发送者，即玩家
+            if game_session.active_games_of(&user) >= game_session.config.max_games_per_player {
+                msg::reply(GameSessionEvent::Error(GameSessionError::TooManyGames), 0)
+                    .expect("回复消息失败");
+                return;
+            }
+            let word_length = match requested_word_length(&action, &game_session.config) {
+                Ok(word_length) => word_length,
+                Err(error) => {
+                    msg::reply(GameSessionEvent::Error(error), 0).expect("回复消息失败");
+                    return;
                 }
-                SessionStatus::WaitUserInput | SessionStatus::WaitWordleCheckWordReply => {
-                    panic!("用户已经在游戏中");
+            };
+            let game_id = game_session.issue_game_id();
+            // 记录当前配置，之后修改配置不影响这局游戏
+            let mut session_info = SessionInfo {
+                user,
+                daily: matches!(action, GameSessionAction::StartDailyGame { .. }),
+                hard_mode,
+                word_length,
+                config: game_session.config.clone(),
+                ..Default::default()
+            };
+            // 向Wordle程序发送"StartGame"消息
+            let send_to_wordle_msg_id = msg::send(
+                session_info.config.wordle_program_id,
+                start_wordle_game(game_id, &session_info),
+                0,
+            )
+            .expect("发送消息失败");
+            session_info.original_msg_id = msg::id(); // 保存初始消息ID
+            session_info.send_to_wordle_msg_id = send_to_wordle_msg_id; // 保存发送到Wordle的消息ID
+            session_info.session_status = SessionStatus::WaitWordleStartReply; // 更新状态为等待Wordle启动回复
+
+            let reply_timeout_blocks = session_info.config.reply_timeout_blocks;
+            let signal_gas = session_info.config.signal_gas;
+            game_session.start_session(game_id, session_info);
+            game_session.waiting.insert(msg::id(), game_id);
+            game_session.requests.insert(send_to_wordle_msg_id, game_id);
+            exec::system_reserve_gas(signal_gas).expect("预留系统 gas 失败");
+            exec::wait_up_to(reply_timeout_blocks); // 等待回复
+        }
+        GameSessionAction::CheckWord { game_id, word }
+        | GameSessionAction::CheckWordV1 { game_id, word } => {
+            if let Some(game_id) = game_session.waiting.remove(&msg::id()) {
+                // 等待期间游戏可能已经超时结束并被压缩
+                let Some(session_info) = game_session.sessions.get_mut(&game_id) else {
+                    return;
+                };
+                match &session_info.session_status {
+                    SessionStatus::ReplyReceived(wordle_event) => {
+                        let wordle_event = wordle_event.clone();
+                        apply_check_word_reply(game_session, game_id, word, &wordle_event, v1);
+                    }
+                    SessionStatus::WaitWordleCheckWordReply => {
+                        // 等待超时或 Wordle 出错后被唤醒，这次猜测不计入尝试次数
+                        reply_wordle_unavailable(game_session, game_id);
+                    }
+                    // 等待期间游戏已经超时结束，玩家已经收到通知
+                    _ => {}
                 }
+                return;
             }
-        }
-        GameSessionAction::CheckWord { word } => {
             let user = msg::source();
-            let session_info = game_session.sessions.entry(user).or_default();
-            match &session_info.session_status {
-                SessionStatus::ReplyReceived(wordle_event) => {
-                    session_info.tries += 1; // 增加尝试次数
-                    if wordle_event.has_guessed() {
-                        // 如果猜对了单词，游戏结束并设置状态为胜利
-                        session_info.session_status = SessionStatus::GameOver(GameStatus::Win);
-                        msg::reply(GameSessionEvent::GameOver(GameStatus::Win), 0)
-                            .expect("回复消息失败");
-                    } else if session_info.tries == TRIES_LIMIT {
-                        // 如果达到尝试次数限制，游戏结束并设置状态为失败
-                        session_info.session_status = SessionStatus::GameOver(GameStatus::Lose);
-                        msg::reply(GameSessionEvent::GameOver(GameStatus::Lose), 0)
-                            .expect("回复消息失败");
-                    } else {
-                        msg::reply::<GameSessionEvent>(wordle_event.into(), 0)
-                            .expect("回复消息失败");
-                        session_info.session_status = SessionStatus::WaitUserInput;
-                        // 更新状态为等待玩家输入
+            // 只能在自己的游戏中猜测
+            let Some(session_info) = game_session
+                .sessions
+                .get_mut(&game_id)
+                .filter(|session_info| session_info.user == user)
+            else {
+                msg::reply(GameSessionEvent::Error(GameSessionError::NotInGame), 0)
+                    .expect("回复消息失败");
+                return;
+            };
+            match session_info.session_status {
+                SessionStatus::WaitUserInput => {
+                    if let Err(error) = check_word(&word, session_info)
+                        .and_then(|()| session_info.check_hard_mode(&word))
+                    {
+                        msg::reply(GameSessionEvent::Error(error), 0).expect("回复消息失败");
+                        return;
                     }
-                }
-                SessionStatus::WaitUserInput | SessionStatus::WaitWordleCheckWordReply => {
-                    // 验证提交的单词长度是否为五，并且所有字母为小写
-                    assert!(
-                        word.len() == 5 && word.chars().all(|c| c.is_lowercase()),
-                        "无效的单词"
-                    );
                     let send_to_wordle_msg_id = msg::send(
-                        game_session.wordle_program_id,
-                        WordleAction::CheckWord { user, word },
+                        session_info.config.wordle_program_id,
+                        WordleAction::CheckWord {
+                            user,
+                            game_id,
+                            word,
+                        },
                         0,
                     )
                     .expect("发送消息失败");
                     session_info.original_msg_id = msg::id();
                     session_info.send_to_wordle_msg_id = send_to_wordle_msg_id;
                     session_info.session_status = SessionStatus::WaitWordleCheckWordReply; // 更新状态为等待Wordle检查单词回复
-                    exec::wait(); // 等待回复
+                    game_session.waiting.insert(msg::id(), game_id);
+                    game_session.requests.insert(send_to_wordle_msg_id, game_id);
+                    exec::system_reserve_gas(session_info.config.signal_gas)
+                        .expect("预留系统 gas 失败");
+                    exec::wait_up_to(session_info.config.reply_timeout_blocks); // 等待回复
                 }
-                SessionStatus::Init
-                | SessionStatus::WaitWordleStartReply
-                | SessionStatus::GameOver(..) => {
-                    panic!("用户不在游戏中");
+                SessionStatus::WaitWordleCheckWordReply => {
+                    msg::reply(GameSessionEvent::Error(GameSessionError::RequestPending), 0)
+                        .expect("回复消息失败");
                 }
-            }
-        }
-        GameSessionAction::CheckGameStatus { user, session_id } => {
-            if msg::source() == exec::program_id() {
-                if let Some(session_info) = game_session.sessions.get_mut(&user) {
-                    if session_id == session_info.session_id
-                        && !matches!(session_info.session_status, SessionStatus::GameOver(..))
-                    {
-                        session_info.session_status = SessionStatus::GameOver(GameStatus::Lose); // 如果时间到未完成，游戏结束并设置状态为失败
-                        msg::send(user, GameSessionEvent::GameOver(GameStatus::Lose), 0)
-                            .expect("发送消息失败");
-                    }
+                _ => {
+                    msg::reply(GameSessionEvent::Error(GameSessionError::NotInGame), 0)
+                        .expect("回复消息失败");
                 }
             }
         }
+        GameSessionAction::CheckGameStatus { game_id } => {
+            check_game_status(game_session, game_id);
+        }
+        GameSessionAction::UpdateConfig(config) => update_config(game_session, config),
+        GameSessionAction::TransferOwnership { new_owner } => {
+            transfer_ownership(game_session, new_owner);
+        }
     }
 }
 
+#[cfg(not(feature = "async"))]
 #[no_mangle]
 extern "C" fn handle_reply() {
     let reply_to = msg::reply_to().expect("查询 reply_to 数据失败");
-    let wordle_event: WordleEvent = msg::load().expect("无法解码 WordleEvent");
     let game_session = get_game_session_mut();
-    let user = wordle_event.get_user();
-    if let Some(session_info) = game_session.sessions.get_mut(user) {
-        if reply_to == session_info.send_to_wordle_msg_id && session_info.is_wait_reply_status() {
-            session_info.session_status = SessionStatus::ReplyReceived(wordle_event); // 收到Wordle程序的回复
+    if forward_revealed_word(game_session, reply_to) {
+        return;
+    }
+    // 放弃等待之后才到的回复已经没有对应的请求
+    let game_id = game_session.requests.remove(&reply_to);
+    if !msg::reply_code().expect("查询回复码失败").is_success() {
+        // Wordle 处理消息时出错（例如 panic 或 gas 不足），唤醒等待的消息，由它回复玩家
+        if let Some(session_info) = game_id
+            .and_then(|game_id| game_session.sessions.get(&game_id))
+            .filter(|session_info| session_info.session_status.is_wait_reply_status())
+        {
             exec::wake(session_info.original_msg_id).expect("唤醒消息失败");
         }
+        return;
     }
+    let wordle_event: WordleEvent = msg::load().expect("无法解码 WordleEvent");
+    if let Some(session_info) = game_id
+        .and_then(|game_id| game_session.sessions.get_mut(&game_id))
+        .filter(|session_info| session_info.session_status.is_wait_reply_status())
+    {
+        session_info.session_status = SessionStatus::ReplyReceived(wordle_event); // 收到Wordle程序的回复
+        exec::wake(session_info.original_msg_id).expect("唤醒消息失败");
+    } else if let WordleEvent::GameStarted { user, game_id, .. } = wordle_event {
+        end_late_started_game(game_session, user, game_id);
+    }
+}
+
+#[cfg(not(feature = "async"))]
+#[no_mangle]
+extern "C" fn handle_signal() {
+    cancel_waiting_session();
 }
 
 #[no_mangle]
 extern "C" fn state() {
+    let query: StateQuery = msg::load().expect("无法解码 StateQuery");
     let game_session = get_game_session();
-    msg::reply::<GameSessionState>(game_session.into(), 0).expect("状态查询回复失败");
+    let reply = match query {
+        StateQuery::Session(game_id) => {
+            StateReply::Session(game_session.sessions.get(&game_id).cloned().map(Box::new))
+        }
+        StateQuery::SessionOf(user) => StateReply::Sessions(game_session.sessions_page(
+            |session_info| session_info.user == user,
+            0,
+            u32::MAX,
+        )),
+        StateQuery::ActiveSessions { offset, limit } => {
+            StateReply::Sessions(game_session.sessions_page(
+                |session_info| session_info.session_status.is_active(),
+                offset,
+                limit,
+            ))
+        }
+        StateQuery::SessionsByStatus(kind) => StateReply::Sessions(game_session.sessions_page(
+            |session_info| session_info.session_status.kind() == kind,
+            0,
+            u32::MAX,
+        )),
+        StateQuery::Config => StateReply::Config {
+            owner: game_session.owner,
+            config: game_session.config.clone(),
+        },
+        StateQuery::Stats(user) => StateReply::Stats(game_session.stats.get(&user).cloned()),
+        StateQuery::Leaderboard { rank_by, limit } => StateReply::Leaderboard(
+            game_session
+                .leaderboard(&rank_by)
+                .into_iter()
+                .take(limit as usize)
+                .collect(),
+        ),
+        StateQuery::PlayerRank { player, rank_by } => StateReply::PlayerRank(
+            game_session
+                .leaderboard(&rank_by)
+                .into_iter()
+                .find(|entry| entry.player == player),
+        ),
+        StateQuery::Storage => StateReply::Storage {
+            live_sessions: game_session.sessions.len() as u32,
+            archived: game_session.archived.clone(),
+        },
+    };
+    msg::reply(reply, 0).expect("状态查询回复失败");
+}
+
+// 处理 Wordle 对 StartGame 的回复，并回复玩家。游戏开始后才安排超时检查
+fn apply_start_reply(game_session: &mut GameSession, game_id: GameId, wordle_event: &WordleEvent) {
+    msg::reply::<GameSessionEvent>(wordle_event.into(), 0).expect("回复消息失败");
+    let session_info = game_session.sessions.get_mut(&game_id).expect("会话不存在");
+    if let WordleEvent::GameStarted {
+        commitment,
+        word_length,
+        alphabet,
+        ..
+    } = wordle_event
+    {
+        // 保存秘密单词的承诺、长度和字母表，并等待玩家输入
+        session_info.commitment = *commitment;
+        session_info.word_length = *word_length;
+        session_info.alphabet = alphabet.clone();
+        session_info.session_status = SessionStatus::WaitUserInput;
+        start_deadlines(game_id, session_info);
+    } else {
+        // Wordle 返回了错误，游戏没有开始，玩家可以重新开始游戏。会话没有可查询的内容，直接删除
+        session_info.session_status = SessionStatus::Init;
+        game_session.archive_session(game_id);
+    }
+}
+
+// 处理 Wordle 对 CheckWord 的回复，记录战绩并回复玩家，`v1` 表示以旧格式回复计分结果
+fn apply_check_word_reply(
+    game_session: &mut GameSession,
+    game_id: GameId,
+    word: String,
+    wordle_event: &WordleEvent,
+    v1: bool,
+) {
+    let session_info = game_session.sessions.get_mut(&game_id).expect("会话不存在");
+    let user = session_info.user;
+    if matches!(
+        wordle_event,
+        WordleEvent::Error(WordleError::ChallengeEnded)
+    ) {
+        // 每日挑战的周期已经结束，Wordle 不再接受这局的猜测，游戏按整局游戏超时结束
+        session_info.session_status = SessionStatus::GameOver(GameStatus::GameTimeout);
+        game_session.stats.entry(user).or_default().record_timeout();
+        msg::reply(
+            GameSessionEvent::GameOver {
+                game_id,
+                status: GameStatus::GameTimeout,
+                guesses: session_info.guesses.clone(),
+            },
+            0,
+        )
+        .expect("回复消息失败");
+        end_wordle_game(game_session, game_id);
+        game_session.finish_session(game_id, exec::block_height());
+        return;
+    }
+    if matches!(
+        wordle_event,
+        WordleEvent::InvalidWord { .. } | WordleEvent::Error(..)
+    ) {
+        // 单词被 Wordle 拒绝，不消耗尝试次数
+        msg::reply::<GameSessionEvent>(wordle_event.into(), 0).expect("回复消息失败");
+        session_info.session_status = SessionStatus::WaitUserInput;
+        return;
+    }
+    session_info.tries += 1; // 增加尝试次数
+    if let WordleEvent::WordChecked { feedback, .. } = wordle_event {
+        // 保存猜测的单词和结果，困难模式据此检查之后的猜测，前端据此还原棋盘
+        session_info.guesses.push(GuessRecord {
+            word,
+            feedback: feedback.clone(),
+            block: exec::block_height(),
+        });
+    }
+    // 根据这次猜测的结果决定游戏是否结束，规则由 wordle-core 统一定义
+    let feedback = session_info
+        .guesses
+        .last()
+        .map_or(&[][..], |guess| &guess.feedback);
+    let outcome = GuessOutcome::of(
+        feedback,
+        session_info.tries,
+        session_info.config.tries_limit,
+    );
+    if outcome == GuessOutcome::Win {
+        // 如果猜对了单词，游戏结束并设置状态为胜利
+        session_info.session_status = SessionStatus::GameOver(GameStatus::Win);
+        game_session
+            .stats
+            .entry(user)
+            .or_default()
+            .record_win(session_info.tries, session_info.config.tries_limit);
+        msg::reply(
+            GameSessionEvent::GameOver {
+                game_id,
+                status: GameStatus::Win,
+                guesses: session_info.guesses.clone(),
+            },
+            0,
+        )
+        .expect("回复消息失败");
+        end_wordle_game(game_session, game_id);
+        game_session.finish_session(game_id, exec::block_height());
+    } else if outcome == GuessOutcome::Lose {
+        // 如果达到尝试次数限制，游戏结束并设置状态为失败
+        session_info.session_status = SessionStatus::GameOver(GameStatus::Lose);
+        game_session.stats.entry(user).or_default().record_loss();
+        msg::reply(
+            GameSessionEvent::GameOver {
+                game_id,
+                status: GameStatus::Lose,
+                guesses: session_info.guesses.clone(),
+            },
+            0,
+        )
+        .expect("回复消息失败");
+        end_wordle_game(game_session, game_id);
+        game_session.finish_session(game_id, exec::block_height());
+    } else {
+        let event: GameSessionEvent = wordle_event.into();
+        msg::reply(if v1 { event.into_v1() } else { event }, 0).expect("回复消息失败");
+        session_info.session_status = SessionStatus::WaitUserInput;
+        // 更新状态为等待玩家输入
+        if matches!(wordle_event, WordleEvent::WordChecked { .. }) {
+            // 猜测计分后才重新计算本次猜测的截止区块
+            reset_guess_deadline(game_id, session_info);
+        }
+    }
+}
+
+// 放弃等待 Wordle 的回复，并通知玩家可以重新发送请求
+fn reply_wordle_unavailable(game_session: &mut GameSession, game_id: GameId) {
+    game_session.cancel_wordle_request(game_id, exec::block_height());
+    msg::reply(GameSessionEvent::WordleUnavailable { game_id }, 0).expect("回复消息失败");
+}
+
+// 游戏开始时计算整局游戏的截止区块，并安排超时检查
+fn start_deadlines(game_id: GameId, session_info: &mut SessionInfo) {
+    let timeout_blocks = session_info.config.timeout_blocks;
+    session_info.game_deadline = exec::block_height().saturating_add(timeout_blocks);
+    session_info.guess_deadline = None;
+    session_info.warned_deadline = None;
+    schedule_deadline_checks(game_id, session_info, timeout_blocks);
+    reset_guess_deadline(game_id, session_info);
+}
+
+// 游戏开始时和每次计分的猜测后重新计算本次猜测的截止区块，并安排超时检查
+fn reset_guess_deadline(game_id: GameId, session_info: &mut SessionInfo) {
+    if let Some(guess_timeout_blocks) = session_info.config.guess_timeout_blocks {
+        session_info.guess_deadline =
+            Some(exec::block_height().saturating_add(guess_timeout_blocks));
+        schedule_deadline_checks(game_id, session_info, guess_timeout_blocks);
+    }
+}
+
+// 在截止区块和发送警告的区块各安排一次超时检查
+fn schedule_deadline_checks(game_id: GameId, session_info: &SessionInfo, delay: u32) {
+    let warning_blocks = session_info.config.warning_blocks;
+    if warning_blocks > 0 && warning_blocks < delay {
+        schedule_game_status_check(game_id, session_info, delay - warning_blocks);
+    }
+    schedule_game_status_check(game_id, session_info, delay);
+}
+
+// 为超时检查预留 gas，由预留的 gas 支付延迟的 CheckGameStatus 消息
+fn schedule_game_status_check(game_id: GameId, session_info: &SessionInfo, delay: u32) {
+    let reservation_id =
+        exec::reserve_gas(session_info.config.check_gas, delay).expect("预留 gas 失败");
+    msg::send_delayed_from_reservation(
+        reservation_id,
+        exec::program_id(),
+        GameSessionAction::CheckGameStatus { game_id },
+        0,
+        delay,
+    )
+    .expect("发送延迟消息失败");
+}
+
+// 每次检查都根据当前区块判断，截止区块推迟后，之前安排的检查不会提前结束游戏
+fn check_game_status(game_session: &mut GameSession, game_id: GameId) {
+    if msg::source() == exec::program_id() {
+        if let Some(session_info) = game_session.sessions.get_mut(&game_id) {
+            if session_info.session_status.is_active() {
+                let user = session_info.user;
+                let now = exec::block_height();
+                let deadline = session_info.deadline();
+                if now >= deadline {
+                    let status = session_info.timeout_status();
+                    session_info.session_status = SessionStatus::GameOver(status.clone()); // 如果时间到未完成，游戏结束并设置状态为超时
+                    game_session.stats.entry(user).or_default().record_timeout();
+                    msg::send(
+                        user,
+                        GameSessionEvent::GameOver {
+                            game_id,
+                            status,
+                            guesses: session_info.guesses.clone(),
+                        },
+                        0,
+                    )
+                    .expect("发送消息失败");
+                    end_wordle_game(game_session, game_id);
+                    game_session.finish_session(game_id, now);
+                } else if deadline - now <= session_info.config.warning_blocks
+                    && session_info.warned_deadline != Some(deadline)
+                {
+                    session_info.warned_deadline = Some(deadline);
+                    msg::send(
+                        user,
+                        GameSessionEvent::TimeoutWarning {
+                            game_id,
+                            blocks_left: deadline - now,
+                        },
+                        0,
+                    )
+                    .expect("发送消息失败");
+                }
+            }
+        }
+    }
+}
+
+fn update_config(game_session: &mut GameSession, config: GameSessionConfig) {
+    let reply = if msg::source() != game_session.owner {
+        GameSessionEvent::Error(GameSessionError::NotOwner)
+    } else if config.validate().is_err() {
+        GameSessionEvent::Error(GameSessionError::InvalidConfig)
+    } else {
+        #[cfg(feature = "async")]
+        async_handle::reserve_signal_gas(&config);
+        game_session.config = config.clone();
+        GameSessionEvent::ConfigUpdated(config)
+    };
+    msg::reply(reply, 0).expect("回复消息失败");
+}
+
+fn transfer_ownership(game_session: &mut GameSession, new_owner: ActorId) {
+    let reply = if msg::source() != game_session.owner {
+        GameSessionEvent::Error(GameSessionError::NotOwner)
+    } else {
+        let previous_owner = game_session.owner;
+        game_session.owner = new_owner;
+        GameSessionEvent::OwnershipTransferred {
+            previous_owner,
+            new_owner,
+        }
+    };
+    msg::reply(reply, 0).expect("回复消息失败");
+}
+
+// 等待 Wordle 回复的消息没能继续执行（例如 gas 耗尽），整理会话并通知玩家
+fn cancel_waiting_session() {
+    let signal_from = msg::signal_from().expect("查询 signal_from 数据失败");
+    let game_session = get_game_session_mut();
+    if let Some((game_id, user)) = game_session
+        .waiting
+        .remove(&signal_from)
+        .and_then(|game_id| Some(game_id).zip(game_session.sessions.get(&game_id)))
+        .filter(|(_, session_info)| session_info.session_status.has_pending_request())
+        .map(|(game_id, session_info)| (game_id, session_info.user))
+    {
+        if matches!(
+            game_session.sessions[&game_id].session_status,
+            SessionStatus::ReplyReceived(WordleEvent::GameStarted { .. })
+        ) {
+            // Wordle 已经开始了这局游戏，但玩家不会再在这局中猜测，让 Wordle 结束它
+            end_wordle_game(game_session, game_id);
+        }
+        game_session.cancel_wordle_request(game_id, exec::block_height());
+        msg::send(user, GameSessionEvent::WordleUnavailable { game_id }, 0).expect("发送消息失败");
+    }
+}
+
+// 放弃等待之后 Wordle 才开始游戏，玩家已经收到 WordleUnavailable，这局游戏不会再进行，
+// 让回复的 Wordle 程序结束它，以免一直占用 Wordle 的存储
+fn end_late_started_game(game_session: &mut GameSession, user: ActorId, game_id: GameId) {
+    send_end_game(game_session, msg::source(), user, game_id);
+}
+
+// 验证单词的长度和字母是否与这局游戏的单词相同，规则与 Wordle 相同，都由 wordle-core 定义
+fn check_word(word: &str, session_info: &SessionInfo) -> Result<(), GameSessionError> {
+    wordle_core::validate_word(word, session_info.word_length, &session_info.alphabet)
+        .map_err(Into::into)
+}
+
+// 玩家选择的单词长度，没有选择长度时使用配置中的单词长度。
+// 每日挑战的长度由 Wordle 决定，开始游戏后用 Wordle 回复的长度替换
+fn requested_word_length(
+    action: &GameSessionAction,
+    config: &GameSessionConfig,
+) -> Result<u8, GameSessionError> {
+    match action {
+        GameSessionAction::StartGame {
+            word_length: Some(word_length),
+            ..
+        } => {
+            if wordle_core::is_supported_length(*word_length) {
+                Ok(*word_length)
+            } else {
+                Err(GameSessionError::InvalidWordLength)
+            }
+        }
+        _ => Ok(config.word_length),
+    }
+}
+
+// 每日挑战和普通游戏只是发给 Wordle 的开始消息不同
+fn start_wordle_game(game_id: GameId, session_info: &SessionInfo) -> WordleAction {
+    let user = session_info.user;
+    if session_info.daily {
+        WordleAction::StartDailyGame { user, game_id }
+    } else {
+        WordleAction::StartGame {
+            user,
+            game_id,
+            word_length: session_info.word_length,
+        }
+    }
+}
+
+// 通知 Wordle 程序游戏结束，Wordle 会在回复中公开秘密单词
+fn end_wordle_game(game_session: &mut GameSession, game_id: GameId) {
+    let session_info = &game_session.sessions[&game_id];
+    let (wordle_program_id, user) = (session_info.config.wordle_program_id, session_info.user);
+    send_end_game(game_session, wordle_program_id, user, game_id);
+}
+
+// 记录发出的 EndGame 消息，收到它的回复时才转发公开的秘密单词
+fn send_end_game(
+    game_session: &mut GameSession,
+    wordle_program_id: ActorId,
+    user: ActorId,
+    game_id: GameId,
+) {
+    let message_id = msg::send(
+        wordle_program_id,
+        WordleAction::EndGame { user, game_id },
+        0,
+    )
+    .expect("发送消息失败");
+    game_session.ending.insert(message_id, wordle_program_id);
+}
+
+// 回复的是发出的 EndGame 消息时返回 true，并把 Wordle 公开的秘密单词
+// （每日挑战则是公开单词的区块）转发给玩家。玩家也能回复发给自己的消息，
+// 所以只相信接收 EndGame 的 Wordle 程序对它的回复
+fn forward_revealed_word(game_session: &mut GameSession, reply_to: MessageId) -> bool {
+    let Some(wordle_program_id) = game_session.ending.remove(&reply_to) else {
+        return false;
+    };
+    if msg::source() == wordle_program_id && msg::reply_code().expect("查询回复码失败").is_success()
+    {
+        if let Ok(
+            wordle_event @ (WordleEvent::GameOver { user, .. }
+            | WordleEvent::GameEnded { user, .. }),
+        ) = msg::load::<WordleEvent>()
+        {
+            msg::send::<GameSessionEvent>(user, (&wordle_event).into(), 0).expect("发送消息失败");
+        }
+    }
+    true
 }
 
+#[allow(static_mut_refs)]
 fn get_game_session_mut() -> &'static mut GameSession {
     unsafe { GAME_SESSION_STATE.as_mut().expect("游戏会话未初始化") }
 }
+#[allow(static_mut_refs)]
 fn get_game_session() -> &'static GameSession {
     unsafe { GAME_SESSION_STATE.as_ref().expect("游戏会话未初始化") }
 }
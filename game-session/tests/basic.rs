@@ -0,0 +1,2412 @@
+use game_session_io::*;
+use gstd::MessageId;
+use gtest::{constants::UNITS, BlockRunResult, Log, Program, ProgramBuilder, System};
+use parity_scale_codec::{Codec, Decode};
+use std::ops::Deref;
+use wordle_io::{commit_word, Action, Event, WordleError, WordleInit, WordleState};
+
+const GAME_SESSION_WASM: &str = "../target/wasm32-gear/debug/game_session.opt.wasm";
+const WORDLE_WASM: &str = "../target/wasm32-gear/debug/wordle.opt.wasm";
+const GAME_SESSION_PROGRAM_ID: u64 = 1;
+const WORDLE_PROGRAM_ID: u64 = 2;
+const USER: u64 = 50; // 学号为 50
+const TRIES_LIMIT: u8 = 5;
+const TIMEOUT_BLOCKS: u32 = 200;
+const WARNING_BLOCKS: u32 = 20;
+const CHECK_GAS: u64 = 5_000_000_000;
+const SIGNAL_GAS: u64 = 1_000_000_000;
+const REPLY_TIMEOUT_BLOCKS: u32 = 20;
+const MAX_GAMES_PER_PLAYER: u32 = 1;
+const DAILY_PERIOD_BLOCKS: u32 = 1_000;
+const RETENTION_BLOCKS: u32 = 1_000;
+// 秘密单词是 horse 时猜测 house 的计分结果
+const HOUSE_FEEDBACK: [LetterFeedback; 5] = [
+    LetterFeedback::Correct,
+    LetterFeedback::Correct,
+    LetterFeedback::Absent,
+    LetterFeedback::Correct,
+    LetterFeedback::Correct,
+];
+
+#[test]
+fn test_win() {
+    let system = new_system();
+    let (game_session_program, _) = setup(&system, |_| {});
+
+    // 尝试在没有开始游戏的情况下检查单词（应该失败）
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "abcde".to_string(),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(GameSessionError::NotInGame));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 开始游戏
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::StartSuccess { game_id: GameId(1) });
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 再次尝试开始游戏（应该失败，同时进行的游戏已经达到上限）
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(GameSessionError::TooManyGames));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 尝试输入无效单词（不符合规则，应该失败）
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "Abcde".to_string(),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(GameSessionError::InvalidCharacters));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "abcdef".to_string(),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(GameSessionError::InvalidWordLength));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 输入合法单词并验证结果
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "house".to_string(),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::CheckWordResult {
+            game_id: GameId(1),
+            feedback: HOUSE_FEEDBACK.to_vec(),
+        });
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 输入正确单词并获胜
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "horse".to_string(),
+        },
+    );
+    assert!(!res.main_failed());
+    let (game_id, status, guesses) = find_game_over(&res, USER).expect("游戏没有结束");
+    assert_eq!((game_id, status), (GameId(1), GameStatus::Win));
+
+    // GameOver 带有所有计分的猜测，无效的单词不在其中
+    let words: Vec<_> = guesses.iter().map(|guess| guess.word.as_str()).collect();
+    assert_eq!(words, vec!["house", "horse"]);
+    assert_eq!(guesses[0].feedback, HOUSE_FEEDBACK);
+    assert!(guesses[0].block < guesses[1].block);
+    assert_eq!(
+        read_session(&game_session_program, GameId(1)).guesses,
+        guesses
+    );
+
+    // 游戏结束后 Wordle 公开秘密单词，可以用游戏开始时的承诺验证
+    let (word, salt) = res
+        .log()
+        .iter()
+        .find_map(|log| match GameSessionEvent::decode(&mut log.payload()) {
+            Ok(GameSessionEvent::WordRevealed { word, salt, .. }) => Some((word, salt)),
+            _ => None,
+        })
+        .expect("秘密单词没有公开");
+    assert_eq!(word, "horse");
+    let session_info = read_session(&game_session_program, GameId(1));
+    assert_eq!(commit_word(&word, &salt), session_info.commitment);
+
+    // 胜利计入玩家战绩，第二次猜中
+    let stats = read_stats(&game_session_program, USER);
+    assert_eq!(
+        stats,
+        PlayerStats {
+            games_played: 1,
+            wins: 1,
+            losses: 0,
+            timeouts: 0,
+            current_streak: 1,
+            max_streak: 1,
+            guess_distribution: vec![0, 1, 0, 0, 0],
+        }
+    );
+
+    // 另一个用户尝试检查单词（没有开始游戏，应该失败）
+    let res = send(
+        &system,
+        &game_session_program,
+        51,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "abcde".to_string(),
+        },
+    );
+    let log = Log::builder()
+        .dest(51)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(GameSessionError::NotInGame));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 输出当前游戏状态
+    let session_info = read_session(&game_session_program, GameId(1));
+    println!("{:?}", session_info);
+}
+
+#[test]
+fn test_tried_limit() {
+    let system = new_system();
+    let (game_session_program, _) = setup(&system, |_| {});
+
+    // 开始游戏
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::StartSuccess { game_id: GameId(1) });
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 测试猜测次数限制
+    for i in 0..5 {
+        let res = send(
+            &system,
+            &game_session_program,
+            USER,
+            GameSessionAction::CheckWord {
+                game_id: GameId(1),
+                word: "house".to_string(),
+            },
+        );
+        if i == 4 {
+            assert!(!res.main_failed());
+            let (_, status, guesses) = find_game_over(&res, USER).expect("游戏没有结束");
+            assert_eq!(status, GameStatus::Lose);
+            assert_eq!(guesses.len(), 5);
+        } else {
+            let log = Log::builder()
+                .dest(USER)
+                .source(GAME_SESSION_PROGRAM_ID)
+                .payload(GameSessionEvent::CheckWordResult {
+                    game_id: GameId(1),
+                    feedback: HOUSE_FEEDBACK.to_vec(),
+                });
+            assert!(!res.main_failed() && res.contains(&log));
+        }
+    }
+    // 输出当前游戏状态
+    let session_info = read_session(&game_session_program, GameId(1));
+    println!("{:?}", session_info);
+
+    // 失败计入玩家战绩
+    let stats = read_stats(&game_session_program, USER);
+    assert_eq!((stats.games_played, stats.losses), (1, 1));
+    assert_eq!(stats.current_streak, 0);
+}
+
+#[test]
+fn test_delayed_logic() {
+    let system = new_system();
+    let (game_session_program, _) = setup(&system, |_| {});
+
+    // 开始游戏
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::StartSuccess { game_id: GameId(1) });
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 超时前 WARNING_BLOCKS 个区块收到警告
+    let result = spend_blocks(&system, TIMEOUT_BLOCKS - WARNING_BLOCKS);
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::TimeoutWarning {
+            game_id: GameId(1),
+            blocks_left: WARNING_BLOCKS,
+        });
+    assert!(result.iter().any(|res| res.contains(&log)));
+
+    // 到达截止区块后游戏以超时结束，而不是失败
+    let result = spend_blocks(&system, WARNING_BLOCKS);
+    let (game_id, status, guesses) = result
+        .iter()
+        .find_map(|res| find_game_over(res, USER))
+        .expect("游戏没有结束");
+    assert_eq!((game_id, status), (GameId(1), GameStatus::GameTimeout));
+    assert!(guesses.is_empty());
+
+    let session_info = read_session(&game_session_program, GameId(1));
+    assert!(matches!(
+        session_info.session_status,
+        SessionStatus::GameOver(GameStatus::GameTimeout)
+    ));
+    let stats = read_stats(&game_session_program, USER);
+    assert_eq!((stats.games_played, stats.timeouts), (1, 1));
+
+    // 玩家回复警告消息，冒充 Wordle 公开另一个玩家的秘密单词，GameSession 不会转发
+    let warning = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::TimeoutWarning {
+            game_id: GameId(1),
+            blocks_left: WARNING_BLOCKS,
+        });
+    system
+        .get_mailbox(USER)
+        .reply(
+            warning,
+            Event::GameOver {
+                user: 51.into(),
+                game_id: GameId(2),
+                word: "fakes".to_string(),
+                salt: [0; 32],
+            },
+            0,
+        )
+        .unwrap();
+    let res = system.run_next_block();
+    assert!(!res.log().iter().any(|log| log.destination() == 51.into()));
+}
+
+#[test]
+fn test_guess_timeout() {
+    let system = new_system();
+    // 两次猜测之间最多间隔 50 个区块
+    let (game_session_program, _) = setup(&system, |config| {
+        config.guess_timeout_blocks = Some(50);
+        config.warning_blocks = 0;
+    });
+
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    assert!(!res.main_failed());
+
+    // 第 30 个区块猜测一次，本次猜测的截止区块推迟到第 80 个区块
+    spend_blocks(&system, 30);
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "house".to_string(),
+        },
+    );
+    assert!(!res.main_failed());
+    let guess_deadline = read_session(&game_session_program, GameId(1)).guess_deadline;
+
+    // 被 Wordle 拒绝的单词不计分，也不推迟截止区块
+    spend_blocks(&system, 10);
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "hxxxx".to_string(),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(GameSessionError::NotInDictionary));
+    assert!(!res.main_failed() && res.contains(&log));
+    let session_info = read_session(&game_session_program, GameId(1));
+    assert_eq!(session_info.guess_deadline, guess_deadline);
+
+    // 原来的截止区块已经过去，游戏仍在进行
+    let result = spend_blocks(&system, 19);
+    assert!(!result.iter().any(|res| find_game_over(res, USER).is_some()));
+    let session_info = read_session(&game_session_program, GameId(1));
+    assert!(matches!(
+        session_info.session_status,
+        SessionStatus::WaitUserInput
+    ));
+
+    // 之后一直不猜测，在新的截止区块超时
+    let result = spend_blocks(&system, 30);
+    let (_, status, guesses) = result
+        .iter()
+        .find_map(|res| find_game_over(res, USER))
+        .expect("游戏没有结束");
+    assert_eq!(status, GameStatus::GuessTimeout);
+    assert_eq!(guesses.len(), 1);
+    let session_info = read_session(&game_session_program, GameId(1));
+    assert!(matches!(
+        session_info.session_status,
+        SessionStatus::GameOver(GameStatus::GuessTimeout)
+    ));
+    let stats = read_stats(&game_session_program, USER);
+    assert_eq!(stats.timeouts, 1);
+}
+
+#[test]
+fn test_reserved_status_checks() {
+    let system = new_system();
+    let (game_session_program, wordle_program) = setup(&system, |_| {});
+
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    assert!(!res.main_failed());
+    let game_deadline = read_session(&game_session_program, GameId(1)).game_deadline;
+
+    // 开始游戏后没有任何人再发送消息，超时检查由开始游戏时预留的 gas 支付，
+    // 在截止区块准时送达，结束游戏并让 Wordle 结束对应的游戏
+    let result = spend_blocks(&system, TIMEOUT_BLOCKS);
+    let res = result
+        .iter()
+        .find(|res| find_game_over(res, USER).is_some())
+        .expect("游戏没有结束");
+    assert_eq!(res.block_info.height, game_deadline);
+    let state: WordleState = wordle_program.read_state(b"").unwrap();
+    assert_eq!(state.active_games, 0);
+}
+
+#[test]
+fn test_insufficient_check_gas() {
+    // 预留的 gas 不够执行超时检查时，延迟消息执行失败，游戏不会超时结束，
+    // 说明超时检查只由预留的 gas 支付
+    let system = new_system();
+    let (game_session_program, _) = setup(&system, |config| config.check_gas = 1_000_000);
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    assert!(!res.main_failed());
+    let result = spend_blocks(&system, TIMEOUT_BLOCKS);
+    assert!(!result.iter().any(|res| find_game_over(res, USER).is_some()));
+    let session_info = read_session(&game_session_program, GameId(1));
+    assert!(session_info.session_status.is_active());
+}
+
+#[test]
+fn test_invalid_word() {
+    let system = new_system();
+    let (game_session_program, wordle_program) = setup(&system, |_| {});
+
+    // 开始游戏
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    assert!(!res.main_failed());
+
+    // 不在词典中的单词被拒绝，且不计入尝试次数
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "aaaaa".to_string(),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(GameSessionError::NotInDictionary));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    let session_info = read_session(&game_session_program, GameId(1));
+    assert_eq!(session_info.tries, 0);
+
+    // 词典中的单词正常计分
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "house".to_string(),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::CheckWordResult {
+            game_id: GameId(1),
+            feedback: HOUSE_FEEDBACK.to_vec(),
+        });
+    assert!(!res.main_failed() && res.contains(&log));
+
+    let session_info = read_session(&game_session_program, GameId(1));
+    assert_eq!(session_info.tries, 1);
+
+    // Wordle 的状态只公开猜测次数和承诺，不公开秘密单词
+    let state: WordleState = wordle_program.read_state(b"").unwrap();
+    assert_eq!(state.active_games, 1);
+    let game_info = state
+        .games
+        .iter()
+        .find(|game_info| game_info.user == USER.into() && game_info.game_id == GameId(1))
+        .unwrap();
+    assert_eq!(game_info.guesses, 1);
+    assert_eq!(game_info.commitment, session_info.commitment);
+}
+
+#[test]
+fn test_word_bank() {
+    let system = new_system();
+    let wordle_program = deploy(&system, WORDLE_WASM, WORDLE_PROGRAM_ID);
+
+    // 单词库中含有无效单词，初始化应该失败
+    let res = send(
+        &system,
+        &wordle_program,
+        USER,
+        wordle_init(&["horse", "Human"]),
+    );
+    assert!(res.main_failed());
+
+    let wordle_program = deploy(&system, WORDLE_WASM, WORDLE_PROGRAM_ID + 1);
+    let res = send(&system, &wordle_program, USER, wordle_init(&["horse"]));
+    assert!(!res.main_failed());
+
+    // 管理员添加单词，重复的单词不计数
+    let res = send(
+        &system,
+        &wordle_program,
+        USER,
+        Action::AddWords(vec!["house".to_string(), "horse".to_string()]),
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(WORDLE_PROGRAM_ID + 1)
+        .payload(Event::WordsAdded { count: 1 });
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 非管理员不能修改单词库
+    let res = send(
+        &system,
+        &wordle_program,
+        51,
+        Action::AddWords(vec!["human".to_string()]),
+    );
+    let log = Log::builder()
+        .dest(51)
+        .source(WORDLE_PROGRAM_ID + 1)
+        .payload(Event::Error(WordleError::NotOwner));
+    assert!(!res.main_failed() && res.contains(&log));
+    let res = send(
+        &system,
+        &wordle_program,
+        51,
+        Action::RemoveWords(vec!["house".to_string()]),
+    );
+    let log = Log::builder()
+        .dest(51)
+        .source(WORDLE_PROGRAM_ID + 1)
+        .payload(Event::Error(WordleError::NotOwner));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 长度或字符不符合要求的单词应该被拒绝
+    let res = send(
+        &system,
+        &wordle_program,
+        USER,
+        Action::AddWords(vec!["elephants".to_string()]),
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(WORDLE_PROGRAM_ID + 1)
+        .payload(Event::Error(WordleError::InvalidWordLength));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 删除单词
+    let res = send(
+        &system,
+        &wordle_program,
+        USER,
+        Action::RemoveWords(vec!["house".to_string()]),
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(WORDLE_PROGRAM_ID + 1)
+        .payload(Event::WordsRemoved { count: 1 });
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 词典可以分块上传，只有管理员可以上传
+    let res = send(
+        &system,
+        &wordle_program,
+        USER,
+        Action::ExtendDictionary(vec!["crane".to_string(), "slate".to_string()]),
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(WORDLE_PROGRAM_ID + 1)
+        .payload(Event::DictionaryExtended { count: 2 });
+    assert!(!res.main_failed() && res.contains(&log));
+    let res = send(
+        &system,
+        &wordle_program,
+        USER,
+        Action::ExtendDictionary(vec!["slate".to_string(), "trace".to_string()]),
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(WORDLE_PROGRAM_ID + 1)
+        .payload(Event::DictionaryExtended { count: 1 });
+    assert!(!res.main_failed() && res.contains(&log));
+    let res = send(
+        &system,
+        &wordle_program,
+        51,
+        Action::ExtendDictionary(vec!["human".to_string()]),
+    );
+    let log = Log::builder()
+        .dest(51)
+        .source(WORDLE_PROGRAM_ID + 1)
+        .payload(Event::Error(WordleError::NotOwner));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    let state: WordleState = wordle_program.read_state(b"").unwrap();
+    assert_eq!(state.bank_sizes, vec![(5, 1)]);
+    assert_eq!(state.dictionary_size, 3);
+    assert_eq!(state.active_games, 0);
+
+    // 单词库不能被清空
+    let res = send(
+        &system,
+        &wordle_program,
+        USER,
+        Action::RemoveWords(vec!["horse".to_string()]),
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(WORDLE_PROGRAM_ID + 1)
+        .payload(Event::Error(WordleError::EmptyBank));
+    assert!(!res.main_failed() && res.contains(&log));
+}
+
+#[test]
+fn test_custom_rules() {
+    let system = new_system();
+    let wordle_program = setup_wordle(&system, wordle_init(&["horse"]), &["house"]);
+
+    // 尝试次数上限为 0 的配置无效，初始化应该失败
+    let game_session_program = deploy(&system, GAME_SESSION_WASM, GAME_SESSION_PROGRAM_ID);
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionInit {
+            tries_limit: 0,
+            ..game_session_init()
+        },
+    );
+    assert!(res.main_failed());
+
+    // 只允许猜两次的 GameSession 程序
+    let game_session_program = deploy(&system, GAME_SESSION_WASM, GAME_SESSION_PROGRAM_ID + 10);
+    // 新的 GameSession 程序需要管理员授权才能在 Wordle 中开始游戏
+    let res = send(
+        &system,
+        &wordle_program,
+        USER,
+        Action::AuthorizeCaller((GAME_SESSION_PROGRAM_ID + 10).into()),
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(WORDLE_PROGRAM_ID)
+        .payload(Event::CallerAuthorized(
+            (GAME_SESSION_PROGRAM_ID + 10).into(),
+        ));
+    assert!(!res.main_failed() && res.contains(&log));
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionInit {
+            tries_limit: 2,
+            ..game_session_init()
+        },
+    );
+    assert!(!res.main_failed());
+
+    let reply: StateReply = game_session_program.read_state(StateQuery::Config).unwrap();
+    let StateReply::Config { config, .. } = reply else {
+        panic!("意外的状态回复: {reply:?}");
+    };
+    assert_eq!(config.tries_limit, 2);
+    assert_eq!(config.timeout_blocks, TIMEOUT_BLOCKS);
+
+    // 开始游戏
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    assert!(!res.main_failed());
+
+    // 第二次猜错后游戏结束，第一次用旧格式返回计分结果
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWordV1 {
+            game_id: GameId(1),
+            word: "house".to_string(),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID + 10)
+        .payload(GameSessionEvent::CheckWordResultV1 {
+            game_id: GameId(1),
+            correct_positions: vec![0, 1, 3, 4],
+            contained_in_word: vec![],
+        });
+    assert!(!res.main_failed() && res.contains(&log));
+
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "house".to_string(),
+        },
+    );
+    assert!(!res.main_failed());
+    let (_, status, _) = find_game_over(&res, USER).expect("游戏没有结束");
+    assert_eq!(status, GameStatus::Lose);
+}
+
+#[test]
+fn test_update_config() {
+    let system = new_system();
+    // 初始化 GameSession 程序，USER 成为管理员
+    let (game_session_program, _) = setup(&system, |_| {});
+    let config = game_session_init();
+
+    // 管理员开始一局游戏
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    assert!(!res.main_failed());
+
+    // 非管理员不能修改配置
+    let new_config = GameSessionConfig {
+        tries_limit: 1,
+        ..config.clone()
+    };
+    let res = send(
+        &system,
+        &game_session_program,
+        51,
+        GameSessionAction::UpdateConfig(new_config.clone()),
+    );
+    let log = Log::builder()
+        .dest(51)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(GameSessionError::NotOwner));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 无效的配置被拒绝
+    let invalid_configs = [
+        GameSessionConfig {
+            word_length: 0,
+            ..config.clone()
+        },
+        GameSessionConfig {
+            signal_gas: 0,
+            ..config.clone()
+        },
+    ];
+    for invalid_config in invalid_configs {
+        let res = send(
+            &system,
+            &game_session_program,
+            USER,
+            GameSessionAction::UpdateConfig(invalid_config),
+        );
+        let log = Log::builder()
+            .dest(USER)
+            .source(GAME_SESSION_PROGRAM_ID)
+            .payload(GameSessionEvent::Error(GameSessionError::InvalidConfig));
+        assert!(!res.main_failed() && res.contains(&log));
+    }
+
+    // 管理员把尝试次数上限改为 1
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::UpdateConfig(new_config.clone()),
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::ConfigUpdated(new_config));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 进行中的游戏仍然使用旧的配置
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "house".to_string(),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::CheckWordResult {
+            game_id: GameId(1),
+            feedback: HOUSE_FEEDBACK.to_vec(),
+        });
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 新开始的游戏使用新的配置，猜错一次就结束
+    let res = send(
+        &system,
+        &game_session_program,
+        51,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    assert!(!res.main_failed());
+    let res = send(
+        &system,
+        &game_session_program,
+        51,
+        GameSessionAction::CheckWord {
+            game_id: GameId(2),
+            word: "house".to_string(),
+        },
+    );
+    assert!(!res.main_failed());
+    let (game_id, status, _) = find_game_over(&res, 51).expect("游戏没有结束");
+    assert_eq!((game_id, status), (GameId(2), GameStatus::Lose));
+
+    // 转移管理员权限后，原管理员不能再修改配置
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::TransferOwnership {
+            new_owner: 51u64.into(),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::OwnershipTransferred {
+            previous_owner: USER.into(),
+            new_owner: 51u64.into(),
+        });
+    assert!(!res.main_failed() && res.contains(&log));
+
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::UpdateConfig(config),
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(GameSessionError::NotOwner));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    let reply: StateReply = game_session_program.read_state(StateQuery::Config).unwrap();
+    let StateReply::Config { owner, config } = reply else {
+        panic!("意外的状态回复: {reply:?}");
+    };
+    assert_eq!(owner, 51u64.into());
+    assert_eq!(config.tries_limit, 1);
+}
+
+#[test]
+fn test_leaderboard() {
+    let system = new_system();
+    // 最多猜两次
+    let (game_session_program, _) = setup(&system, |config| config.tries_limit = 2);
+
+    // USER 第二次猜对，51 两局都第一次猜对，52 输掉一局
+    let games: [(u64, &[&str]); 4] = [
+        (USER, &["house", "horse"]),
+        (51, &["horse"]),
+        (51, &["horse"]),
+        (52, &["house", "house"]),
+    ];
+    // 游戏 id 按开始的顺序从 1 开始分配
+    for (game_id, (player, words)) in (1..).zip(games) {
+        let res = send(
+            &system,
+            &game_session_program,
+            player,
+            GameSessionAction::StartGame {
+                hard_mode: false,
+                word_length: None,
+            },
+        );
+        assert!(!res.main_failed());
+        for word in words {
+            let res = send(
+                &system,
+                &game_session_program,
+                player,
+                GameSessionAction::CheckWord {
+                    game_id: GameId(game_id),
+                    word: word.to_string(),
+                },
+            );
+            assert!(!res.main_failed());
+        }
+    }
+
+    // 按胜局数排名
+    let reply: StateReply = game_session_program
+        .read_state(StateQuery::Leaderboard {
+            rank_by: RankBy::Wins,
+            limit: 10,
+        })
+        .unwrap();
+    let StateReply::Leaderboard(entries) = reply else {
+        panic!("意外的状态回复: {reply:?}");
+    };
+    let players: Vec<_> = entries.iter().map(|entry| entry.player).collect();
+    assert_eq!(players, vec![51u64.into(), USER.into(), 52u64.into()]);
+    assert_eq!(
+        entries[0],
+        LeaderboardEntry {
+            rank: 1,
+            player: 51u64.into(),
+            games_played: 2,
+            wins: 2,
+            total_guesses: 2,
+        }
+    );
+
+    // 只返回前 N 名
+    let reply: StateReply = game_session_program
+        .read_state(StateQuery::Leaderboard {
+            rank_by: RankBy::Wins,
+            limit: 1,
+        })
+        .unwrap();
+    let StateReply::Leaderboard(entries) = reply else {
+        panic!("意外的状态回复: {reply:?}");
+    };
+    assert_eq!(entries.len(), 1);
+
+    // 按胜率排名，只有玩过至少两局的玩家参与
+    let reply: StateReply = game_session_program
+        .read_state(StateQuery::Leaderboard {
+            rank_by: RankBy::WinRate { min_games: 2 },
+            limit: 10,
+        })
+        .unwrap();
+    let StateReply::Leaderboard(entries) = reply else {
+        panic!("意外的状态回复: {reply:?}");
+    };
+    let players: Vec<_> = entries.iter().map(|entry| entry.player).collect();
+    assert_eq!(players, vec![51u64.into()]);
+
+    // 按平均猜测次数排名，没有赢过的玩家不参与
+    let reply: StateReply = game_session_program
+        .read_state(StateQuery::Leaderboard {
+            rank_by: RankBy::AverageGuesses,
+            limit: 10,
+        })
+        .unwrap();
+    let StateReply::Leaderboard(entries) = reply else {
+        panic!("意外的状态回复: {reply:?}");
+    };
+    let players: Vec<_> = entries.iter().map(|entry| entry.player).collect();
+    assert_eq!(players, vec![51u64.into(), USER.into()]);
+
+    // 查询单个玩家的名次
+    let reply: StateReply = game_session_program
+        .read_state(StateQuery::PlayerRank {
+            player: USER.into(),
+            rank_by: RankBy::Wins,
+        })
+        .unwrap();
+    let StateReply::PlayerRank(Some(entry)) = reply else {
+        panic!("意外的状态回复: {reply:?}");
+    };
+    assert_eq!(entry.rank, 2);
+    assert_eq!(entry.wins, 1);
+    assert_eq!(entry.total_guesses, 2);
+
+    let reply: StateReply = game_session_program
+        .read_state(StateQuery::PlayerRank {
+            player: 52u64.into(),
+            rank_by: RankBy::AverageGuesses,
+        })
+        .unwrap();
+    assert!(matches!(reply, StateReply::PlayerRank(None)));
+}
+
+#[test]
+fn test_state_queries() {
+    let system = new_system();
+    let (game_session_program, _) = setup_with(&system, wordle_init(&["horse"]), &[], |_| {});
+
+    // 三个玩家依次开始游戏 1、2、3，52 第一次就猜中
+    for player in [USER, 51, 52] {
+        let res = send(
+            &system,
+            &game_session_program,
+            player,
+            GameSessionAction::StartGame {
+                hard_mode: false,
+                word_length: None,
+            },
+        );
+        assert!(!res.main_failed());
+    }
+    let res = send(
+        &system,
+        &game_session_program,
+        52,
+        GameSessionAction::CheckWord {
+            game_id: GameId(3),
+            word: "horse".to_string(),
+        },
+    );
+    assert!(!res.main_failed());
+
+    // 分页查询进行中的会话
+    let reply: StateReply = game_session_program
+        .read_state(StateQuery::ActiveSessions {
+            offset: 0,
+            limit: 10,
+        })
+        .unwrap();
+    let StateReply::Sessions(sessions) = reply else {
+        panic!("意外的状态回复: {reply:?}");
+    };
+    let game_ids: Vec<_> = sessions.iter().map(|(game_id, _)| *game_id).collect();
+    assert_eq!(game_ids, vec![GameId(1), GameId(2)]);
+
+    let reply: StateReply = game_session_program
+        .read_state(StateQuery::ActiveSessions {
+            offset: 1,
+            limit: 1,
+        })
+        .unwrap();
+    let StateReply::Sessions(sessions) = reply else {
+        panic!("意外的状态回复: {reply:?}");
+    };
+    let game_ids: Vec<_> = sessions.iter().map(|(game_id, _)| *game_id).collect();
+    assert_eq!(game_ids, vec![GameId(2)]);
+
+    // 按状态筛选会话
+    let reply: StateReply = game_session_program
+        .read_state(StateQuery::SessionsByStatus(SessionStatusKind::GameOver))
+        .unwrap();
+    let StateReply::Sessions(sessions) = reply else {
+        panic!("意外的状态回复: {reply:?}");
+    };
+    let game_ids: Vec<_> = sessions.iter().map(|(game_id, _)| *game_id).collect();
+    assert_eq!(game_ids, vec![GameId(3)]);
+    assert_eq!(sessions[0].1.user, 52u64.into());
+
+    // 没有玩过的玩家没有会话，没有结束过游戏的玩家没有战绩
+    let reply: StateReply = game_session_program
+        .read_state(StateQuery::SessionOf(53u64.into()))
+        .unwrap();
+    assert!(matches!(reply, StateReply::Sessions(sessions) if sessions.is_empty()));
+    let reply: StateReply = game_session_program
+        .read_state(StateQuery::Session(GameId(4)))
+        .unwrap();
+    assert!(matches!(reply, StateReply::Session(None)));
+    let reply: StateReply = game_session_program
+        .read_state(StateQuery::Stats(USER.into()))
+        .unwrap();
+    assert!(matches!(reply, StateReply::Stats(None)));
+    assert_eq!(read_stats(&game_session_program, 52).wins, 1);
+    assert_eq!(read_session(&game_session_program, GameId(2)).tries, 0);
+}
+
+#[test]
+fn test_wordle_unavailable() {
+    let system = new_system();
+    // Wordle 地址是一个不会按时回复的账户
+    let silent_wordle = 100;
+    system.mint_to(silent_wordle, 10_000 * UNITS);
+    let (game_session_program, _) = setup(&system, |config| {
+        config.wordle_program_id = silent_wordle.into()
+    });
+
+    // 开始游戏后一直等不到 Wordle 的回复
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    assert!(!res.main_failed());
+    let session_info = read_session(&game_session_program, GameId(1));
+    assert!(matches!(
+        session_info.session_status,
+        SessionStatus::WaitWordleStartReply
+    ));
+
+    // 超过 REPLY_TIMEOUT_BLOCKS 后放弃等待，并通知玩家
+    let result = spend_blocks(&system, REPLY_TIMEOUT_BLOCKS);
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::WordleUnavailable { game_id: GameId(1) });
+    assert!(result.iter().any(|res| res.contains(&log)));
+
+    // 会话回到初始状态，玩家可以重新开始游戏
+    let session_info = read_session(&game_session_program, GameId(1));
+    assert!(matches!(session_info.session_status, SessionStatus::Init));
+
+    // Wordle 之后才回复游戏已经开始，GameSession 让它结束这局游戏
+    let start = Log::builder()
+        .source(GAME_SESSION_PROGRAM_ID)
+        .dest(silent_wordle)
+        .payload(Action::StartGame {
+            user: USER.into(),
+            game_id: GameId(1),
+            word_length: 5,
+        });
+    system
+        .get_mailbox(silent_wordle)
+        .reply(
+            start,
+            Event::GameStarted {
+                user: USER.into(),
+                game_id: GameId(1),
+                commitment: [0; 32],
+                word_length: 5,
+                alphabet: Alphabet::english(),
+            },
+            0,
+        )
+        .unwrap();
+    let res = system.run_next_block();
+    let log = Log::builder()
+        .source(GAME_SESSION_PROGRAM_ID)
+        .dest(silent_wordle)
+        .payload(Action::EndGame {
+            user: USER.into(),
+            game_id: GameId(1),
+        });
+    assert!(res.contains(&log));
+    let session_info = read_session(&game_session_program, GameId(1));
+    assert!(matches!(session_info.session_status, SessionStatus::Init));
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    assert!(!res.main_failed());
+}
+
+#[test]
+fn test_wordle_failed() {
+    let system = new_system();
+    let (game_session_program, _) = setup(&system, |_| {});
+
+    // 另一个 Wordle 程序初始化失败，发给它的消息都以错误回复
+    let broken_wordle = 3;
+    let broken_wordle_program = deploy(&system, WORDLE_WASM, broken_wordle);
+    let res = send(
+        &system,
+        &broken_wordle_program,
+        USER,
+        WordleInit {
+            daily_period_blocks: 0,
+            ..wordle_init(&["horse"])
+        },
+    );
+    assert!(res.main_failed());
+    let config = GameSessionConfig {
+        wordle_program_id: broken_wordle.into(),
+        ..game_session_init()
+    };
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::UpdateConfig(config),
+    );
+    assert!(!res.main_failed());
+
+    // Wordle 出错时放弃这次请求，并通知玩家
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    assert!(!res.main_failed());
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::WordleUnavailable { game_id: GameId(1) });
+    assert!(res.contains(&log));
+    let session_info = read_session(&game_session_program, GameId(1));
+    assert!(matches!(session_info.session_status, SessionStatus::Init));
+
+    // 换回正常的 Wordle 后，玩家可以重新开始游戏
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::UpdateConfig(game_session_init()),
+    );
+    assert!(!res.main_failed());
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::StartSuccess { game_id: GameId(2) });
+    assert!(!res.main_failed() && res.contains(&log));
+}
+
+#[test]
+fn test_handle_signal() {
+    let system = new_system();
+    // 超时检查预留的 gas 比开始游戏的消息带的 gas 还多，
+    // 为 handle_signal 预留的 gas 足够加载程序并通知玩家
+    let silent_wordle = 100;
+    system.mint_to(silent_wordle, 10_000 * UNITS);
+    let (game_session_program, _) = setup(&system, |config| {
+        config.wordle_program_id = silent_wordle.into();
+        config.check_gas = 20 * CHECK_GAS;
+        config.signal_gas = 5 * SIGNAL_GAS;
+    });
+
+    let message_id = game_session_program.send_with_gas(
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+        50_000_000_000,
+        0,
+    );
+    let res = system.run_next_block();
+    assert!(!res.failed.contains(&message_id));
+
+    // Wordle 开始了游戏，被唤醒的消息没有足够的 gas 预留超时检查，执行失败
+    let start = Log::builder()
+        .source(GAME_SESSION_PROGRAM_ID)
+        .dest(silent_wordle)
+        .payload(Action::StartGame {
+            user: USER.into(),
+            game_id: GameId(1),
+            word_length: 5,
+        });
+    system
+        .get_mailbox(silent_wordle)
+        .reply(
+            start,
+            Event::GameStarted {
+                user: USER.into(),
+                game_id: GameId(1),
+                commitment: [0; 32],
+                word_length: 5,
+                alphabet: Alphabet::english(),
+            },
+            0,
+        )
+        .unwrap();
+    let res = system.run_next_block();
+    assert!(res.failed.contains(&message_id));
+
+    // handle_signal 放弃这次请求并通知玩家，会话不再占用玩家的游戏数量
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::WordleUnavailable { game_id: GameId(1) });
+    assert!(res.contains(&log));
+    let session_info = read_session(&game_session_program, GameId(1));
+    assert!(matches!(session_info.session_status, SessionStatus::Init));
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    assert!(!res.main_failed());
+    let session_info = read_session(&game_session_program, GameId(2));
+    assert!(matches!(
+        session_info.session_status,
+        SessionStatus::WaitWordleStartReply
+    ));
+}
+
+#[test]
+fn test_concurrent_games() {
+    let system = new_system();
+    // 每个玩家最多同时进行两局游戏
+    let (game_session_program, wordle_program) =
+        setup_with(&system, wordle_init(&["horse"]), &[], |config| {
+            config.max_games_per_player = 2
+        });
+
+    // 同一个玩家开始两局游戏，每局有自己的 id
+    for game_id in [GameId(1), GameId(2)] {
+        let res = send(
+            &system,
+            &game_session_program,
+            USER,
+            GameSessionAction::StartGame {
+                hard_mode: false,
+                word_length: None,
+            },
+        );
+        let log = Log::builder()
+            .dest(USER)
+            .source(GAME_SESSION_PROGRAM_ID)
+            .payload(GameSessionEvent::StartSuccess { game_id });
+        assert!(!res.main_failed() && res.contains(&log));
+    }
+
+    // 第三局超过上限
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(GameSessionError::TooManyGames));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 其他玩家不能在这个玩家的游戏中猜测
+    let res = send(
+        &system,
+        &game_session_program,
+        51,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "horse".to_string(),
+        },
+    );
+    let log = Log::builder()
+        .dest(51)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(GameSessionError::NotInGame));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 赢下第二局，第一局不受影响
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(2),
+            word: "horse".to_string(),
+        },
+    );
+    assert!(!res.main_failed());
+    let (game_id, status, _) = find_game_over(&res, USER).expect("游戏没有结束");
+    assert_eq!((game_id, status), (GameId(2), GameStatus::Win));
+    let session_info = read_session(&game_session_program, GameId(1));
+    assert!(matches!(
+        session_info.session_status,
+        SessionStatus::WaitUserInput
+    ));
+    assert_eq!(session_info.tries, 0);
+
+    // 结束一局后又可以开始新的游戏
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::StartSuccess { game_id: GameId(3) });
+    assert!(!res.main_failed() && res.contains(&log));
+
+    let reply: StateReply = game_session_program
+        .read_state(StateQuery::SessionOf(USER.into()))
+        .unwrap();
+    let StateReply::Sessions(sessions) = reply else {
+        panic!("意外的状态回复: {reply:?}");
+    };
+    let game_ids: Vec<_> = sessions.iter().map(|(game_id, _)| *game_id).collect();
+    assert_eq!(game_ids, vec![GameId(1), GameId(2), GameId(3)]);
+
+    // Wordle 中只剩下进行中的两局
+    let state: WordleState = wordle_program.read_state(b"").unwrap();
+    assert_eq!(state.active_games, 2);
+}
+
+// 创建测试环境，给发送消息的玩家铸造余额，用来支付消息的 gas
+fn new_system() -> System {
+    let system = System::new();
+    system.init_logger();
+    for player in [USER, 51, 52] {
+        system.mint_to(player, 10_000 * UNITS);
+    }
+    system
+}
+
+// 部署程序，还没有初始化
+fn deploy<'a>(system: &'a System, wasm: &str, id: u64) -> Program<'a> {
+    ProgramBuilder::from_file(wasm).with_id(id).build(system)
+}
+
+// Wordle 的默认初始化参数，单词库只有 words 中的单词
+fn wordle_init(words: &[&str]) -> WordleInit {
+    WordleInit {
+        words: words.iter().map(|word| word.to_string()).collect(),
+        daily_word_length: 5,
+        alphabet: Alphabet::english(),
+        daily_period_blocks: DAILY_PERIOD_BLOCKS,
+        authorized_callers: vec![GAME_SESSION_PROGRAM_ID.into()],
+    }
+}
+
+// GameSession 的默认配置
+fn game_session_init() -> GameSessionInit {
+    GameSessionInit {
+        wordle_program_id: WORDLE_PROGRAM_ID.into(),
+        tries_limit: TRIES_LIMIT,
+        timeout_blocks: TIMEOUT_BLOCKS,
+        guess_timeout_blocks: None,
+        warning_blocks: WARNING_BLOCKS,
+        check_gas: CHECK_GAS,
+        signal_gas: SIGNAL_GAS,
+        reply_timeout_blocks: REPLY_TIMEOUT_BLOCKS,
+        word_length: 5,
+        max_games_per_player: MAX_GAMES_PER_PLAYER,
+        retention_blocks: RETENTION_BLOCKS,
+    }
+}
+
+// 部署并初始化 Wordle 程序，再把 dictionary 中的单词加入词典
+fn setup_wordle<'a>(system: &'a System, init: WordleInit, dictionary: &[&str]) -> Program<'a> {
+    let wordle_program = deploy(system, WORDLE_WASM, WORDLE_PROGRAM_ID);
+    let res = send(system, &wordle_program, USER, init);
+    assert!(!res.main_failed());
+    if !dictionary.is_empty() {
+        let words = dictionary.iter().map(|word| word.to_string()).collect();
+        let res = send(
+            system,
+            &wordle_program,
+            USER,
+            Action::ExtendDictionary(words),
+        );
+        assert!(!res.main_failed());
+    }
+    wordle_program
+}
+
+// 部署并初始化两个程序，返回 (game_session, wordle)。
+// Wordle 的单词库只有 horse，保证结果可预测，词典中另有 house；
+// configure 在初始化 GameSession 之前修改默认配置
+fn setup(
+    system: &System,
+    configure: impl FnOnce(&mut GameSessionInit),
+) -> (Program<'_>, Program<'_>) {
+    setup_with(system, wordle_init(&["horse"]), &["house"], configure)
+}
+
+// 同 setup，但用 wordle 初始化 Wordle 程序，词典中另有 dictionary 中的单词
+fn setup_with<'a>(
+    system: &'a System,
+    wordle: WordleInit,
+    dictionary: &[&str],
+    configure: impl FnOnce(&mut GameSessionInit),
+) -> (Program<'a>, Program<'a>) {
+    let wordle_program = setup_wordle(system, wordle, dictionary);
+    let game_session_program = deploy(system, GAME_SESSION_WASM, GAME_SESSION_PROGRAM_ID);
+    let mut config = game_session_init();
+    configure(&mut config);
+    let res = send(system, &game_session_program, USER, config);
+    assert!(!res.main_failed());
+    (game_session_program, wordle_program)
+}
+
+// 一条消息和处理它的区块的运行结果
+struct RunResult {
+    message_id: MessageId,
+    block: BlockRunResult,
+}
+
+impl RunResult {
+    // 消息本身是否执行失败
+    fn main_failed(&self) -> bool {
+        self.block.failed.contains(&self.message_id)
+    }
+}
+
+impl Deref for RunResult {
+    type Target = BlockRunResult;
+
+    fn deref(&self) -> &BlockRunResult {
+        &self.block
+    }
+}
+
+// 发送消息并运行一个区块，消息以及它引起的回复都在这个区块中处理
+fn send(system: &System, program: &Program, from: u64, payload: impl Codec) -> RunResult {
+    let message_id = program.send(from, payload);
+    RunResult {
+        message_id,
+        block: system.run_next_block(),
+    }
+}
+
+// 运行 blocks 个区块，返回每个区块的运行结果
+fn spend_blocks(system: &System, blocks: u32) -> Vec<BlockRunResult> {
+    system.run_to_block(system.block_height() + blocks)
+}
+
+// 读取一局游戏的会话
+fn read_session(program: &Program, game_id: GameId) -> SessionInfo {
+    let reply: StateReply = program.read_state(StateQuery::Session(game_id)).unwrap();
+    let StateReply::Session(Some(session_info)) = reply else {
+        panic!("意外的状态回复: {reply:?}");
+    };
+    *session_info
+}
+
+// 在运行结果中查找发给玩家的 GameOver 事件，猜测记录带有区块号，所以不直接比较整个事件
+fn find_game_over(
+    res: &BlockRunResult,
+    user: u64,
+) -> Option<(GameId, GameStatus, Vec<GuessRecord>)> {
+    res.log()
+        .iter()
+        .filter(|log| log.destination() == user.into())
+        .find_map(|log| match GameSessionEvent::decode(&mut log.payload()) {
+            Ok(GameSessionEvent::GameOver {
+                game_id,
+                status,
+                guesses,
+            }) => Some((game_id, status, guesses)),
+            _ => None,
+        })
+}
+
+// 读取玩家的战绩
+fn read_stats(program: &Program, user: u64) -> PlayerStats {
+    let reply: StateReply = program.read_state(StateQuery::Stats(user.into())).unwrap();
+    let StateReply::Stats(Some(stats)) = reply else {
+        panic!("意外的状态回复: {reply:?}");
+    };
+    stats
+}
+
+#[test]
+fn test_daily_challenge() {
+    let system = new_system();
+    // 只能猜一次，猜一次游戏就结束
+    let (game_session_program, wordle_program) = setup_with(
+        &system,
+        wordle_init(&["horse", "house", "human"]),
+        &[],
+        |config| config.tries_limit = 1,
+    );
+
+    // 两个玩家参加同一个周期的每日挑战，单词的承诺相同
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartDailyGame { hard_mode: false },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::StartSuccess { game_id: GameId(1) });
+    assert!(!res.main_failed() && res.contains(&log));
+    let res = send(
+        &system,
+        &game_session_program,
+        51,
+        GameSessionAction::StartDailyGame { hard_mode: false },
+    );
+    assert!(!res.main_failed());
+    let commitment = read_session(&game_session_program, GameId(1)).commitment;
+    assert_eq!(
+        read_session(&game_session_program, GameId(2)).commitment,
+        commitment
+    );
+
+    // 游戏结束后不公开单词，只告知公开单词的区块
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "horse".to_string(),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::WordHidden {
+            game_id: GameId(1),
+            reveal_at: DAILY_PERIOD_BLOCKS,
+        });
+    assert!(!res.main_failed() && res.contains(&log));
+    assert!(!res.log().iter().any(|log| matches!(
+        GameSessionEvent::decode(&mut log.payload()),
+        Ok(GameSessionEvent::WordRevealed { .. })
+    )));
+
+    // 同一个周期内不能再玩一次
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartDailyGame { hard_mode: false },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(GameSessionError::Wordle(
+            WordleError::AlreadyPlayed,
+        )));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    let state: WordleState = wordle_program.read_state(b"").unwrap();
+    assert_eq!(state.daily.len(), 1);
+    assert_eq!(state.daily[0].players, 2);
+    assert_eq!(state.daily[0].commitment, commitment);
+    assert!(state.daily[0].revealed.is_none());
+
+    // 周期结束后所有人都能在 Wordle 的状态中看到单词
+    spend_blocks(&system, DAILY_PERIOD_BLOCKS);
+    let state: WordleState = wordle_program.read_state(b"").unwrap();
+    let (word, salt) = state.daily[0].revealed.clone().expect("单词没有公开");
+    assert_eq!(commit_word(&word, &salt), commitment);
+
+    // 新的周期可以再玩一次
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartDailyGame { hard_mode: false },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::StartSuccess { game_id: GameId(4) });
+    assert!(!res.main_failed() && res.contains(&log));
+    let state: WordleState = wordle_program.read_state(b"").unwrap();
+    assert_eq!(state.daily.len(), 2);
+    assert_eq!(state.daily[1].period, state.current_period);
+}
+
+#[test]
+fn test_daily_period_ends() {
+    let system = new_system();
+    let (game_session_program, _) = setup(&system, |_| {});
+
+    // 在周期结束前 10 个区块开始每日挑战，整局游戏的截止区块在下一个周期
+    spend_blocks(&system, DAILY_PERIOD_BLOCKS - 10 - system.block_height());
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartDailyGame { hard_mode: false },
+    );
+    assert!(!res.main_failed());
+    spend_blocks(&system, 10);
+
+    // 周期结束后 Wordle 拒绝猜测，游戏结束，不用等到截止区块
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "horse".to_string(),
+        },
+    );
+    assert!(!res.main_failed());
+    let (game_id, status, guesses) = find_game_over(&res, USER).expect("游戏没有结束");
+    assert_eq!((game_id, status), (GameId(1), GameStatus::GameTimeout));
+    assert!(guesses.is_empty());
+    let stats = read_stats(&game_session_program, USER);
+    assert_eq!((stats.games_played, stats.timeouts), (1, 1));
+
+    // 周期已经结束，Wordle 公开了单词，玩家可以开始新的游戏
+    let session_info = read_session(&game_session_program, GameId(1));
+    let (word, salt) = res
+        .log()
+        .iter()
+        .find_map(|log| match GameSessionEvent::decode(&mut log.payload()) {
+            Ok(GameSessionEvent::WordRevealed { word, salt, .. }) => Some((word, salt)),
+            _ => None,
+        })
+        .expect("秘密单词没有公开");
+    assert_eq!(commit_word(&word, &salt), session_info.commitment);
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartDailyGame { hard_mode: false },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::StartSuccess { game_id: GameId(2) });
+    assert!(!res.main_failed() && res.contains(&log));
+}
+
+#[test]
+fn test_hard_mode() {
+    let system = new_system();
+    let (game_session_program, _) = setup_with(
+        &system,
+        wordle_init(&["horse"]),
+        &["shore", "house"],
+        |_| {},
+    );
+
+    // 以困难模式开始游戏
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: true,
+            word_length: None,
+        },
+    );
+    assert!(!res.main_failed());
+
+    // "shore" 确定了第 4 个位置的 e，s、h、o、r 都在单词中
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "shore".to_string(),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::CheckWordResult {
+            game_id: GameId(1),
+            feedback: vec![
+                LetterFeedback::Present,
+                LetterFeedback::Present,
+                LetterFeedback::Present,
+                LetterFeedback::Present,
+                LetterFeedback::Correct,
+            ],
+        });
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // "house" 没有使用已知在单词中的 r，被拒绝且不计入尝试次数
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "house".to_string(),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(
+            GameSessionError::MissingPresentLetter {
+                letter: "r".to_string(),
+            },
+        ));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // "horsa" 没有把确定的 e 留在原位
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "horsa".to_string(),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(
+            GameSessionError::MissingCorrectLetter {
+                position: 4,
+                letter: "e".to_string(),
+            },
+        ));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    let session_info = read_session(&game_session_program, GameId(1));
+    assert_eq!(session_info.tries, 1);
+    assert_eq!(session_info.guesses.len(), 1);
+    assert_eq!(session_info.guesses[0].word, "shore");
+
+    // 使用了所有提示的猜测正常计分
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "horse".to_string(),
+        },
+    );
+    assert!(!res.main_failed());
+    let (_, status, guesses) = find_game_over(&res, USER).expect("游戏没有结束");
+    assert_eq!(status, GameStatus::Win);
+    assert_eq!(guesses.len(), 2);
+}
+
+#[test]
+fn test_unauthorized_caller() {
+    let system = new_system();
+    let (game_session_program, wordle_program) =
+        setup_with(&system, wordle_init(&["horse"]), &[], |_| {});
+
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    assert!(!res.main_failed());
+
+    // 攻击者直接给 Wordle 发消息，试图重置玩家的单词或者绕过尝试次数上限
+    let attacks = [
+        Action::StartGame {
+            user: USER.into(),
+            game_id: GameId(1),
+            word_length: 5,
+        },
+        Action::CheckWord {
+            user: USER.into(),
+            game_id: GameId(1),
+            word: "horse".to_string(),
+        },
+        Action::EndGame {
+            user: USER.into(),
+            game_id: GameId(1),
+        },
+    ];
+    for attack in attacks {
+        let res = send(&system, &wordle_program, 51, attack);
+        let log = Log::builder()
+            .dest(51)
+            .source(WORDLE_PROGRAM_ID)
+            .payload(Event::Error(WordleError::NotAuthorized));
+        assert!(!res.main_failed() && res.contains(&log));
+    }
+
+    // 玩家的游戏没有受到影响
+    let state: WordleState = wordle_program.read_state(b"").unwrap();
+    assert_eq!(state.active_games, 1);
+    assert_eq!(state.games[0].guesses, 0);
+    assert_eq!(
+        state.games[0].commitment,
+        read_session(&game_session_program, GameId(1)).commitment
+    );
+
+    // 只有管理员可以授权
+    let res = send(
+        &system,
+        &wordle_program,
+        51,
+        Action::AuthorizeCaller(51u64.into()),
+    );
+    let log = Log::builder()
+        .dest(51)
+        .source(WORDLE_PROGRAM_ID)
+        .payload(Event::Error(WordleError::NotOwner));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 撤销授权后 GameSession 程序也不能继续游戏
+    let res = send(
+        &system,
+        &wordle_program,
+        USER,
+        Action::RevokeCaller(GAME_SESSION_PROGRAM_ID.into()),
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(WORDLE_PROGRAM_ID)
+        .payload(Event::CallerRevoked(GAME_SESSION_PROGRAM_ID.into()));
+    assert!(!res.main_failed() && res.contains(&log));
+    let state: WordleState = wordle_program.read_state(b"").unwrap();
+    assert!(state.authorized_callers.is_empty());
+
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "horse".to_string(),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(GameSessionError::Wordle(
+            WordleError::NotAuthorized,
+        )));
+    assert!(!res.main_failed() && res.contains(&log));
+}
+
+#[test]
+fn test_session_compaction() {
+    let system = new_system();
+    // 游戏结束的会话保留 10 个区块
+    let (game_session_program, wordle_program) =
+        setup_with(&system, wordle_init(&["horse"]), &[], |config| {
+            config.retention_blocks = 10
+        });
+
+    // 玩一局并猜中
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    assert!(!res.main_failed());
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "horse".to_string(),
+        },
+    );
+    assert!(!res.main_failed());
+    let (_, status, _) = find_game_over(&res, USER).expect("没有收到 GameOver");
+    assert_eq!(status, GameStatus::Win);
+
+    // Wordle 收到 EndGame 后删除了这局游戏
+    let state: WordleState = wordle_program.read_state(b"").unwrap();
+    assert_eq!(state.active_games, 0);
+    assert_eq!(state.ended_games, 1);
+
+    // Wordle 没有 6 个字母的单词库，开始失败的会话立即被删除并计入汇总
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: Some(6),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(GameSessionError::Wordle(
+            WordleError::UnsupportedWordLength,
+        )));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 保留期内会话仍然可以查询
+    let reply: StateReply = game_session_program
+        .read_state(StateQuery::Storage)
+        .unwrap();
+    let StateReply::Storage {
+        live_sessions,
+        archived,
+    } = reply
+    else {
+        panic!("意外的状态回复: {reply:?}");
+    };
+    assert_eq!(live_sessions, 1);
+    assert_eq!(
+        archived,
+        ArchiveSummary {
+            sessions: 1,
+            abandoned: 1,
+            ..Default::default()
+        }
+    );
+    let reply: StateReply = game_session_program
+        .read_state(StateQuery::Session(GameId(2)))
+        .unwrap();
+    assert!(matches!(reply, StateReply::Session(None)));
+    assert!(matches!(
+        read_session(&game_session_program, GameId(1)).session_status,
+        SessionStatus::GameOver(GameStatus::Win)
+    ));
+
+    // 保留期过后开始新游戏时，旧会话被压缩进汇总
+    spend_blocks(&system, 10);
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::StartSuccess { game_id: GameId(3) });
+    assert!(!res.main_failed() && res.contains(&log));
+
+    let reply: StateReply = game_session_program
+        .read_state(StateQuery::Storage)
+        .unwrap();
+    let StateReply::Storage {
+        live_sessions,
+        archived,
+    } = reply
+    else {
+        panic!("意外的状态回复: {reply:?}");
+    };
+    assert_eq!(live_sessions, 1);
+    assert_eq!(
+        archived,
+        ArchiveSummary {
+            sessions: 2,
+            wins: 1,
+            losses: 0,
+            timeouts: 0,
+            abandoned: 1,
+            total_guesses: 1,
+        }
+    );
+    for game_id in [GameId(1), GameId(2)] {
+        let reply: StateReply = game_session_program
+            .read_state(StateQuery::Session(game_id))
+            .unwrap();
+        assert!(matches!(reply, StateReply::Session(None)));
+    }
+
+    // 玩家的战绩不受压缩影响
+    assert_eq!(read_stats(&game_session_program, USER).wins, 1);
+}
+
+#[test]
+fn test_daily_word_length() {
+    let system = new_system();
+    // Wordle 每日挑战的单词有 4 个字母，与 GameSession 配置的默认长度不同
+    let (game_session_program, _) = setup_with(
+        &system,
+        WordleInit {
+            daily_word_length: 4,
+            ..wordle_init(&["horse", "tree"])
+        },
+        &[],
+        |_| {},
+    );
+
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartDailyGame { hard_mode: false },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::StartSuccess { game_id: GameId(1) });
+    assert!(!res.main_failed() && res.contains(&log));
+    // 会话使用 Wordle 回复的单词长度
+    assert_eq!(
+        read_session(&game_session_program, GameId(1)).word_length,
+        4
+    );
+
+    // 猜测按 4 个字母检查
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "tree".to_string(),
+        },
+    );
+    assert!(!res.main_failed());
+    let (_, status, _) = find_game_over(&res, USER).expect("没有收到 GameOver");
+    assert_eq!(status, GameStatus::Win);
+}
+
+#[test]
+fn test_russian_alphabet() {
+    let system = new_system();
+    // 俄语单词库，每个字母占两个字节，但单词长度按字母计算
+    let (game_session_program, _) = setup_with(
+        &system,
+        WordleInit {
+            daily_word_length: 4,
+            alphabet: Alphabet::russian(),
+            ..wordle_init(&["ёлка"])
+        },
+        &["елка"],
+        |config| config.word_length = 4,
+    );
+
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: None,
+        },
+    );
+    assert!(!res.main_failed());
+
+    // 拉丁字母和大写字母不在字母表中，字母数不对的单词长度无效
+    let invalid_words = [
+        ("elka", GameSessionError::InvalidCharacters),
+        ("Ёлка", GameSessionError::InvalidCharacters),
+        ("ёлк", GameSessionError::InvalidWordLength),
+        ("ёлочка", GameSessionError::InvalidWordLength),
+    ];
+    for (word, error) in invalid_words {
+        let res = send(
+            &system,
+            &game_session_program,
+            USER,
+            GameSessionAction::CheckWord {
+                game_id: GameId(1),
+                word: word.to_string(),
+            },
+        );
+        let log = Log::builder()
+            .dest(USER)
+            .source(GAME_SESSION_PROGRAM_ID)
+            .payload(GameSessionEvent::Error(error));
+        assert!(!res.main_failed() && res.contains(&log));
+    }
+
+    // 按字母计分：只有第一个字母不同
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "елка".to_string(),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::CheckWordResult {
+            game_id: GameId(1),
+            feedback: vec![
+                LetterFeedback::Absent,
+                LetterFeedback::Correct,
+                LetterFeedback::Correct,
+                LetterFeedback::Correct,
+            ],
+        });
+    assert!(!res.main_failed() && res.contains(&log));
+
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "ёлка".to_string(),
+        },
+    );
+    assert!(!res.main_failed());
+    let (_, status, guesses) = find_game_over(&res, USER).expect("没有收到 GameOver");
+    assert_eq!(status, GameStatus::Win);
+    assert_eq!(guesses.len(), 2);
+}
+
+#[test]
+fn test_word_lengths() {
+    let system = new_system();
+    // 每个长度的单词放进各自的单词库，每个库只有一个单词，保证结果可预测
+    let (game_session_program, wordle_program) = setup_with(
+        &system,
+        wordle_init(&["horse", "tree", "planet"]),
+        &[],
+        |config| config.max_games_per_player = 3,
+    );
+
+    // 不选择长度时使用配置中的长度，也可以选择其他有单词库的长度
+    for (game_id, word_length) in [(GameId(1), None), (GameId(2), Some(4))] {
+        let res = send(
+            &system,
+            &game_session_program,
+            USER,
+            GameSessionAction::StartGame {
+                hard_mode: false,
+                word_length,
+            },
+        );
+        let log = Log::builder()
+            .dest(USER)
+            .source(GAME_SESSION_PROGRAM_ID)
+            .payload(GameSessionEvent::StartSuccess { game_id });
+        assert!(!res.main_failed() && res.contains(&log));
+    }
+    assert_eq!(
+        read_session(&game_session_program, GameId(1)).word_length,
+        5
+    );
+    assert_eq!(
+        read_session(&game_session_program, GameId(2)).word_length,
+        4
+    );
+
+    // Wordle 没有 7 个字母的单词库
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: Some(7),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(GameSessionError::Wordle(
+            WordleError::UnsupportedWordLength,
+        )));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 超出 4 到 8 个字母的长度直接被拒绝
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::StartGame {
+            hard_mode: false,
+            word_length: Some(9),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(GameSessionError::InvalidWordLength));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 每局游戏按自己的单词长度检查猜测
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(1),
+            word: "tree".to_string(),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(GameSessionError::InvalidWordLength));
+    assert!(!res.main_failed() && res.contains(&log));
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(2),
+            word: "horse".to_string(),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(GAME_SESSION_PROGRAM_ID)
+        .payload(GameSessionEvent::Error(GameSessionError::InvalidWordLength));
+    assert!(!res.main_failed() && res.contains(&log));
+
+    // 4 个字母的游戏猜中 4 个字母的单词即获胜
+    let res = send(
+        &system,
+        &game_session_program,
+        USER,
+        GameSessionAction::CheckWord {
+            game_id: GameId(2),
+            word: "tree".to_string(),
+        },
+    );
+    assert!(!res.main_failed());
+    let (game_id, status, guesses) = find_game_over(&res, USER).expect("没有收到 GameOver");
+    assert_eq!(game_id, GameId(2));
+    assert_eq!(status, GameStatus::Win);
+    assert_eq!(guesses[0].feedback, vec![LetterFeedback::Correct; 4]);
+
+    // 删除 6 个字母的单词后这个长度不能再开始游戏，每日挑战的单词库不能被清空
+    let res = send(
+        &system,
+        &wordle_program,
+        USER,
+        Action::RemoveWords(vec!["planet".to_string()]),
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(WORDLE_PROGRAM_ID)
+        .payload(Event::WordsRemoved { count: 1 });
+    assert!(!res.main_failed() && res.contains(&log));
+    let res = send(
+        &system,
+        &wordle_program,
+        USER,
+        Action::RemoveWords(vec!["horse".to_string()]),
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .source(WORDLE_PROGRAM_ID)
+        .payload(Event::Error(WordleError::EmptyBank));
+    assert!(!res.main_failed() && res.contains(&log));
+    let state: WordleState = wordle_program.read_state(b"").unwrap();
+    assert_eq!(state.bank_sizes, vec![(4, 1), (5, 1)]);
+}
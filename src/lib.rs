@@ -1,105 +1,346 @@
-#![no_std]
-use gstd::{collections::HashMap, exec, msg, prelude::*, ActorId};
-use wordle_io::*;
+//! Rules of the game shared by the Wordle and game-session programs and by
+//! off-chain clients: word validation, scoring, the feedback types, the
+//! checks a session runs on every guess and the states a session goes through.
+//!
+//! Words are handled as sequences of Unicode scalar values (`char`s), so a
+//! bank can use any alphabet. Words are compared as they are, without Unicode
+//! normalization: the bank and the guesses must use the same form, e.g. NFC.
+//!
+//! The crate is `no_std` unless the default `std` feature is enabled.
 
-pub struct WordleMetadata;
+#![cfg_attr(not(feature = "std"), no_std)]
 
-impl Metadata for WordleMetadata {
-    type Init = ();
-    type Handle = InOut<Action, Event>;
-    type Others = ();
-    type Reply = ();
-    type Signal = ();
-    type State = ();
+extern crate alloc;
+
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum LetterFeedback {
+    /// The letter is in the word at this position.
+    Correct,
+    /// The letter is in the word at another position.
+    Present,
+    Absent,
 }
 
-static mut WORDLE: Option<Wordle> = None;
+/// The letters the words of a bank are made of.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct Alphabet {
+    pub letters: String,
+}
 
-const BANK_OF_WORDS: [&str; 3] = ["house", "human", "horse"];
+impl Alphabet {
+    pub fn new(letters: &str) -> Self {
+        Self {
+            letters: letters.to_string(),
+        }
+    }
+
+    /// Lowercase `a` to `z`.
+    pub fn english() -> Self {
+        Self::new("abcdefghijklmnopqrstuvwxyz")
+    }
+
+    pub fn german() -> Self {
+        Self::new("abcdefghijklmnopqrstuvwxyzäöüß")
+    }
 
-#[derive(Default)]
-struct Wordle {
-    games: HashMap<ActorId, String>,
+    pub fn spanish() -> Self {
+        Self::new("abcdefghijklmnopqrstuvwxyzáéíñóúü")
+    }
+
+    pub fn russian() -> Self {
+        Self::new("абвгдеёжзийклмнопрстуфхцчшщъыьэюя")
+    }
+
+    pub fn contains(&self, letter: char) -> bool {
+        self.letters.contains(letter)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.letters.is_empty()
+    }
 }
 
-#[no_mangle]
-extern "C" fn init() {
-    unsafe {
-        WORDLE = Some(Wordle {
-            games: HashMap::new(),
-        });
+impl Default for Alphabet {
+    fn default() -> Self {
+        Self::english()
     }
 }
 
-#[no_mangle]
-extern "C" fn handle() {
-    let action: Action = msg::load().expect("Unable to decode ");
-    let wordle = unsafe { WORDLE.as_mut().expect("The program is not initialized") };
+/// Shortest word a bank may hold.
+pub const MIN_WORD_LENGTH: u8 = 4;
+/// Longest word a bank may hold.
+pub const MAX_WORD_LENGTH: u8 = 8;
 
-    let reply = match action {
-        Action::StartGame { user } => {
-            let random_id = get_random_value(BANK_OF_WORDS.len() as u8);
-            let word = BANK_OF_WORDS[random_id as usize];
-            wordle.games.insert(user, word.to_string());
-            Event::GameStarted { user }
+/// Whether games can be played on words of `word_length` letters.
+pub fn is_supported_length(word_length: u8) -> bool {
+    (MIN_WORD_LENGTH..=MAX_WORD_LENGTH).contains(&word_length)
+}
+
+/// Why a word can't be played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordError {
+    InvalidWordLength,
+    InvalidCharacters,
+}
+
+/// Checks that `word` has exactly `word_length` letters, all of them from
+/// `alphabet`. Letters are counted as `char`s, not bytes.
+pub fn validate_word(word: &str, word_length: u8, alphabet: &Alphabet) -> Result<(), WordError> {
+    if word.chars().count() != word_length as usize {
+        return Err(WordError::InvalidWordLength);
+    }
+    if !word.chars().all(|letter| alphabet.contains(letter)) {
+        return Err(WordError::InvalidCharacters);
+    }
+    Ok(())
+}
+
+/// Checks a word of any supported length, e.g. one added to a bank, and
+/// returns its length.
+pub fn checked_word_length(word: &str, alphabet: &Alphabet) -> Result<u8, WordError> {
+    let word_length = u8::try_from(word.chars().count())
+        .ok()
+        .filter(|word_length| is_supported_length(*word_length))
+        .ok_or(WordError::InvalidWordLength)?;
+    validate_word(word, word_length, alphabet)?;
+    Ok(word_length)
+}
+
+/// Scores `guess` against `secret` using standard Wordle rules.
+///
+/// Returns one [`LetterFeedback`] per letter of `guess`. Exact matches are
+/// handed out first; a misplaced letter is reported as present only while the
+/// secret still has an unmatched copy of it, so repeated letters are never
+/// over-counted.
+pub fn score_word(secret: &str, guess: &str) -> Vec<LetterFeedback> {
+    let secret: Vec<char> = secret.chars().collect();
+    let guess: Vec<char> = guess.chars().collect();
+
+    let mut feedback = vec![LetterFeedback::Absent; guess.len()];
+    let mut unmatched: BTreeMap<char, u8> = BTreeMap::new();
+
+    for (i, letter) in secret.iter().enumerate() {
+        if guess.get(i) == Some(letter) {
+            feedback[i] = LetterFeedback::Correct;
+        } else {
+            *unmatched.entry(*letter).or_default() += 1;
+        }
+    }
+
+    for (i, letter) in guess.iter().enumerate() {
+        if feedback[i] == LetterFeedback::Correct {
+            continue;
         }
-        Action::CheckWord { user, word } => {
-            if word.len() != 5 {
-                panic!("The length of the word exceeds 5");
+        if let Some(count) = unmatched.get_mut(letter).filter(|count| **count > 0) {
+            *count -= 1;
+            feedback[i] = LetterFeedback::Present;
+        }
+    }
+
+    feedback
+}
+
+/// Converts per-letter feedback to the `(correct_positions, contained_in_word)`
+/// index lists used by the V1 actions.
+pub fn feedback_positions(feedback: &[LetterFeedback]) -> (Vec<u8>, Vec<u8>) {
+    let positions = |kind| {
+        feedback
+            .iter()
+            .enumerate()
+            .filter(|(_, letter)| **letter == kind)
+            .map(|(i, _)| i as u8)
+            .collect()
+    };
+    (
+        positions(LetterFeedback::Correct),
+        positions(LetterFeedback::Present),
+    )
+}
+
+/// Whether the guess that got `feedback` is the secret word.
+pub fn is_solved(feedback: &[LetterFeedback]) -> bool {
+    !feedback.is_empty()
+        && feedback
+            .iter()
+            .all(|letter| *letter == LetterFeedback::Correct)
+}
+
+/// A scored guess of a session.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct GuessRecord {
+    pub word: String,
+    /// Feedback for each letter of `word`.
+    pub feedback: Vec<LetterFeedback>,
+    /// Block in which the guess was scored.
+    pub block: u32,
+}
+
+/// A guess that ignores a hint revealed earlier in a hard mode game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HintError {
+    /// `letter` was found at `position` and has to stay there.
+    MissingCorrectLetter { position: u8, letter: String },
+    /// `letter` is known to be in the word and has to be used.
+    MissingPresentLetter { letter: String },
+}
+
+impl GuessRecord {
+    // A correct letter has to stay in place, and a present letter has to
+    // appear at least as many times as this guess showed it in the word.
+    fn check_hints(&self, word: &[char]) -> Result<(), HintError> {
+        let guess: Vec<(char, LetterFeedback)> = self
+            .word
+            .chars()
+            .zip(self.feedback.iter().copied())
+            .collect();
+        for (position, &(letter, feedback)) in guess.iter().enumerate() {
+            if feedback == LetterFeedback::Correct && word.get(position) != Some(&letter) {
+                return Err(HintError::MissingCorrectLetter {
+                    position: position as u8,
+                    letter: letter.to_string(),
+                });
             }
-            let key_word = wordle
-                .games
-                .get(&user)
-                .expect("There is no game with this user");
-            let mut matched_indices = Vec::with_capacity(5);
-            let mut key_indices = Vec::with_capacity(5);
-            for (i, (a, b)) in key_word.chars().zip(word.chars()).enumerate() {
-                if a == b {
-                    matched_indices.push(i as u8);
-                } else if key_word.contains(b) {
-                    key_indices.push(i as u8);
-                }
+        }
+        for &(letter, feedback) in &guess {
+            if feedback != LetterFeedback::Present {
+                continue;
             }
-
-            Event::WordChecked {
-                user,
-                correct_positions: matched_indices,
-                contained_in_word: key_indices,
+            let required = guess
+                .iter()
+                .filter(|&&(c, feedback)| c == letter && feedback != LetterFeedback::Absent)
+                .count();
+            if word.iter().filter(|&&c| c == letter).count() < required {
+                return Err(HintError::MissingPresentLetter {
+                    letter: letter.to_string(),
+                });
             }
         }
-    };
+        Ok(())
+    }
+}
 
-    msg::reply(reply, 0).expect("Error in sending a reply");
+/// Checks that `word` uses every hint of the earlier `guesses`, as hard mode
+/// requires.
+pub fn check_hard_mode(guesses: &[GuessRecord], word: &str) -> Result<(), HintError> {
+    let word: Vec<char> = word.chars().collect();
+    guesses
+        .iter()
+        .try_for_each(|guess| guess.check_hints(&word))
 }
 
-static mut SEED: u8 = 0;
+/// Where a session goes after a guess is scored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuessOutcome {
+    Win,
+    /// The last allowed guess missed the word.
+    Lose,
+    /// The player can guess again.
+    Continue,
+}
+
+impl GuessOutcome {
+    /// The outcome of the guess that got `feedback`, given that it was the
+    /// `tries`-th of at most `tries_limit`.
+    pub fn of(feedback: &[LetterFeedback], tries: u8, tries_limit: u8) -> Self {
+        if is_solved(feedback) {
+            GuessOutcome::Win
+        } else if tries >= tries_limit {
+            GuessOutcome::Lose
+        } else {
+            GuessOutcome::Continue
+        }
+    }
+}
+
+/// How a game run by a game-session program ended.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum GameStatus {
+    Win,
+    Lose,
+    /// The deadline of the whole game passed, or the period of a daily game
+    /// ended before it was solved.
+    GameTimeout,
+    /// The deadline of the current guess passed.
+    GuessTimeout,
+}
 
-pub fn get_random_value(range: u8) -> u8 {
-    let seed = unsafe { SEED };
-    unsafe { SEED = SEED.wrapping_add(1) };
-    let mut random_input: [u8; 32] = exec::program_id().into();
-    random_input[0] = random_input[0].wrapping_add(seed);
-    let (random, _) = exec::random(random_input).expect("Error in getting random number");
-    random[0] % range
+/// The state of a session of a game-session program, which plays a game of
+/// the Wordle program on behalf of a user. `E` is the reply of the Wordle
+/// program.
+#[derive(Default, Debug, Clone, Encode, Decode, TypeInfo)]
+pub enum SessionStatus<E> {
+    /// The game hasn't started, or the Wordle program didn't start it.
+    #[default]
+    Init,
+    WaitUserInput,
+    WaitWordleStartReply,
+    WaitWordleCheckWordReply,
+    /// The reply arrived and the request waiting for it is yet to handle it.
+    ReplyReceived(E),
+    GameOver(GameStatus),
 }
 
-pub enum Action {
-    StartGame {
-        user: ActorId,
-    },
-    CheckWord {
-        user: ActorId,
-        word: String,
+impl<E> SessionStatus<E> {
+    pub fn kind(&self) -> SessionStatusKind {
+        match self {
+            SessionStatus::Init => SessionStatusKind::Init,
+            SessionStatus::WaitUserInput => SessionStatusKind::WaitUserInput,
+            SessionStatus::WaitWordleStartReply => SessionStatusKind::WaitWordleStartReply,
+            SessionStatus::WaitWordleCheckWordReply => SessionStatusKind::WaitWordleCheckWordReply,
+            SessionStatus::ReplyReceived(..) => SessionStatusKind::ReplyReceived,
+            SessionStatus::GameOver(..) => SessionStatusKind::GameOver,
+        }
+    }
+
+    /// Whether the game is being played.
+    pub fn is_active(&self) -> bool {
+        !matches!(self, SessionStatus::Init | SessionStatus::GameOver(..))
+    }
+
+    /// Whether the session waits for the Wordle program to reply.
+    pub fn is_wait_reply_status(&self) -> bool {
+        matches!(
+            self,
+            SessionStatus::WaitWordleStartReply | SessionStatus::WaitWordleCheckWordReply
+        )
+    }
+
+    /// Whether a request to the Wordle program isn't finished: the session
+    /// waits for the reply, or the reply arrived and the request waiting for
+    /// it is yet to handle it.
+    pub fn has_pending_request(&self) -> bool {
+        self.is_wait_reply_status() || matches!(self, SessionStatus::ReplyReceived(..))
+    }
+
+    /// Gives up waiting for the Wordle program: a start request goes back to
+    /// [`SessionStatus::Init`], a guess back to [`SessionStatus::WaitUserInput`].
+    /// A reply that arrived but wasn't handled is dropped the same way;
+    /// `started` tells whether it answers a guess in a started game.
+    pub fn cancel_wordle_request(&mut self, started: bool) {
+        *self = match self {
+            SessionStatus::WaitWordleStartReply => SessionStatus::Init,
+            SessionStatus::ReplyReceived(..) if !started => SessionStatus::Init,
+            _ => SessionStatus::WaitUserInput,
+        };
     }
 }
 
-pub enum Event {
-    GameStarted {
-        user: ActorId,
-    },
-    WordChecked {
-        user: ActorId,
-        correct_positions: Vec<u8>,
-        contained_in_word: Vec<u8>,
-    },
+/// A [`SessionStatus`] without its data, to filter sessions by status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum SessionStatusKind {
+    Init,
+    WaitUserInput,
+    WaitWordleStartReply,
+    WaitWordleCheckWordReply,
+    ReplyReceived,
+    GameOver,
 }
@@ -0,0 +1,255 @@
+use wordle_core::{
+    check_hard_mode, checked_word_length, feedback_positions, is_supported_length, score_word,
+    validate_word, Alphabet, GameStatus, GuessOutcome, GuessRecord, HintError, LetterFeedback,
+    SessionStatus, SessionStatusKind, WordError,
+};
+
+// (secret, guess, correct_positions, contained_in_word)
+const CASES: &[(&str, &str, &[u8], &[u8])] = &[
+    // No repeated letters
+    ("house", "house", &[0, 1, 2, 3, 4], &[]),
+    ("horse", "house", &[0, 1, 3, 4], &[]),
+    ("abcde", "fghij", &[], &[]),
+    ("crane", "nacre", &[4], &[0, 1, 2, 3]),
+    // Repeated letter in the guess, single copy in the secret
+    ("house", "eerie", &[4], &[]),
+    ("crane", "eerie", &[4], &[2]),
+    ("llama", "hello", &[], &[2, 3]),
+    ("steal", "speed", &[0, 2], &[]),
+    // Repeated letter in the secret, single copy in the guess
+    ("hello", "llama", &[], &[0, 1]),
+    ("speed", "abide", &[], &[3, 4]),
+    ("speed", "erase", &[], &[0, 3, 4]),
+    // Repeated letters on both sides
+    ("abbey", "babes", &[2, 3], &[0, 1]),
+    ("abbey", "kebab", &[2], &[1, 3, 4]),
+    ("eerie", "level", &[1], &[3]),
+    ("speed", "crepe", &[2], &[3, 4]),
+    ("mamma", "maxim", &[0, 1], &[4]),
+    // Tripled letters
+    ("abbey", "bbbbb", &[1, 2], &[]),
+    ("eerie", "eeeee", &[0, 1, 4], &[]),
+    ("eeeaa", "aeeee", &[1, 2], &[0, 3]),
+    ("aaabb", "bbaaa", &[2], &[0, 1, 3, 4]),
+];
+
+#[test]
+fn test_score_word_table() {
+    for &(secret, guess, correct_positions, contained_in_word) in CASES {
+        let (correct, contained) = feedback_positions(&score_word(secret, guess));
+        assert_eq!(
+            correct, correct_positions,
+            "correct_positions for {guess} against {secret}"
+        );
+        assert_eq!(
+            contained, contained_in_word,
+            "contained_in_word for {guess} against {secret}"
+        );
+    }
+}
+
+#[test]
+fn test_score_word_feedback() {
+    use LetterFeedback::*;
+
+    assert_eq!(
+        score_word("crane", "eerie"),
+        vec![Absent, Absent, Present, Absent, Correct]
+    );
+    assert_eq!(
+        score_word("horse", "house"),
+        vec![Correct, Correct, Absent, Correct, Correct]
+    );
+    // Every letter gets an entry, whatever the length of the word
+    assert_eq!(score_word("cat", "act"), vec![Present, Present, Correct]);
+}
+
+#[test]
+fn test_validate_word() {
+    let english = Alphabet::english();
+    let validate = |word| validate_word(word, 5, &english);
+    assert_eq!(validate("horse"), Ok(()));
+    assert_eq!(validate("hors"), Err(WordError::InvalidWordLength));
+    assert_eq!(validate("horses"), Err(WordError::InvalidWordLength));
+    assert_eq!(validate("Horse"), Err(WordError::InvalidCharacters));
+    assert_eq!(validate("hor5e"), Err(WordError::InvalidCharacters));
+    // Letters outside the alphabet are rejected, and counted as one letter each
+    assert_eq!(validate("hørse"), Err(WordError::InvalidCharacters));
+}
+
+#[test]
+fn test_validate_word_alphabets() {
+    // Cyrillic letters take two bytes each, but are counted once
+    let russian = Alphabet::russian();
+    assert_eq!(validate_word("ёлка", 4, &russian), Ok(()));
+    assert_eq!(validate_word("слово", 5, &russian), Ok(()));
+    assert_eq!(
+        validate_word("ёлка", 8, &russian),
+        Err(WordError::InvalidWordLength)
+    );
+    assert_eq!(
+        validate_word("Ёлка", 4, &russian),
+        Err(WordError::InvalidCharacters)
+    );
+    assert_eq!(
+        validate_word("elka", 4, &russian),
+        Err(WordError::InvalidCharacters)
+    );
+
+    let german = Alphabet::german();
+    assert_eq!(validate_word("über", 4, &german), Ok(()));
+    assert_eq!(validate_word("straße", 6, &german), Ok(()));
+    assert_eq!(
+        validate_word("über", 4, &Alphabet::english()),
+        Err(WordError::InvalidCharacters)
+    );
+
+    let spanish = Alphabet::spanish();
+    assert_eq!(validate_word("niño", 4, &spanish), Ok(()));
+    assert_eq!(validate_word("canción", 7, &spanish), Ok(()));
+
+    // A custom alphabet
+    let binary = Alphabet::new("01");
+    assert_eq!(validate_word("0110", 4, &binary), Ok(()));
+    assert_eq!(
+        validate_word("0120", 4, &binary),
+        Err(WordError::InvalidCharacters)
+    );
+}
+
+#[test]
+fn test_word_lengths() {
+    assert!(!is_supported_length(3));
+    assert!((4..=8).all(is_supported_length));
+    assert!(!is_supported_length(9));
+
+    let english = Alphabet::english();
+    assert_eq!(checked_word_length("tree", &english), Ok(4));
+    assert_eq!(checked_word_length("horse", &english), Ok(5));
+    assert_eq!(checked_word_length("elephant", &english), Ok(8));
+    assert_eq!(
+        checked_word_length("cat", &english),
+        Err(WordError::InvalidWordLength)
+    );
+    assert_eq!(
+        checked_word_length("elephants", &english),
+        Err(WordError::InvalidWordLength)
+    );
+    assert_eq!(
+        checked_word_length("Horse", &english),
+        Err(WordError::InvalidCharacters)
+    );
+    // Letters are counted as chars: 4 letters, 8 bytes
+    assert_eq!(checked_word_length("ёлка", &Alphabet::russian()), Ok(4));
+
+    // Scoring and the win condition follow the length of the word
+    let feedback = score_word("planet", "planes");
+    assert_eq!(feedback.len(), 6);
+    assert_eq!(GuessOutcome::of(&feedback, 1, 6), GuessOutcome::Continue);
+    let feedback = score_word("tree", "tree");
+    assert_eq!(feedback, vec![LetterFeedback::Correct; 4]);
+    assert_eq!(GuessOutcome::of(&feedback, 1, 6), GuessOutcome::Win);
+}
+
+#[test]
+fn test_score_word_unicode() {
+    use LetterFeedback::*;
+
+    assert_eq!(
+        score_word("ёлка", "елка"),
+        vec![Absent, Correct, Correct, Correct]
+    );
+    assert_eq!(
+        score_word("ёлка", "калё"),
+        vec![Present, Present, Present, Present]
+    );
+    assert_eq!(
+        score_word("über", "rübe"),
+        vec![Present, Present, Present, Present]
+    );
+    // Positions are letter indexes, not byte offsets
+    assert_eq!(
+        feedback_positions(&score_word("straße", "strauß")),
+        (vec![0, 1, 2, 3], vec![5])
+    );
+}
+
+fn record(secret: &str, guess: &str) -> GuessRecord {
+    GuessRecord {
+        word: guess.to_string(),
+        feedback: score_word(secret, guess),
+        block: 0,
+    }
+}
+
+#[test]
+fn test_check_hard_mode() {
+    let guesses = [record("horse", "house")];
+    assert_eq!(check_hard_mode(&guesses, "horse"), Ok(()));
+    assert_eq!(
+        check_hard_mode(&guesses, "mouse"),
+        Err(HintError::MissingCorrectLetter {
+            position: 0,
+            letter: "h".to_string(),
+        })
+    );
+
+    // The secret has two e's and the guess showed both of them
+    let guesses = [record("speed", "erase")];
+    assert_eq!(check_hard_mode(&guesses, "deeps"), Ok(()));
+    assert_eq!(
+        check_hard_mode(&guesses, "spend"),
+        Err(HintError::MissingPresentLetter {
+            letter: "e".to_string(),
+        })
+    );
+
+    // Without earlier guesses anything goes
+    assert_eq!(check_hard_mode(&[], "zzzzz"), Ok(()));
+}
+
+#[test]
+fn test_guess_outcome() {
+    let win = score_word("horse", "horse");
+    let miss = score_word("horse", "house");
+
+    assert_eq!(GuessOutcome::of(&win, 1, 5), GuessOutcome::Win);
+    assert_eq!(GuessOutcome::of(&win, 5, 5), GuessOutcome::Win);
+    assert_eq!(GuessOutcome::of(&miss, 4, 5), GuessOutcome::Continue);
+    assert_eq!(GuessOutcome::of(&miss, 5, 5), GuessOutcome::Lose);
+    // A guess that wasn't scored never wins
+    assert_eq!(GuessOutcome::of(&[], 1, 5), GuessOutcome::Continue);
+}
+
+#[test]
+fn test_session_status() {
+    // A start request that is given up leaves the game unstarted
+    let mut status = SessionStatus::<()>::WaitWordleStartReply;
+    assert!(status.is_active() && status.is_wait_reply_status());
+    status.cancel_wordle_request(false);
+    assert_eq!(status.kind(), SessionStatusKind::Init);
+    assert!(!status.is_active());
+
+    // A guess that is given up lets the player guess again
+    let mut status = SessionStatus::<()>::WaitWordleCheckWordReply;
+    status.cancel_wordle_request(true);
+    assert_eq!(status.kind(), SessionStatusKind::WaitUserInput);
+    assert!(status.is_active() && !status.is_wait_reply_status());
+
+    // A reply that wasn't handled is dropped with the request it answers
+    let mut status = SessionStatus::ReplyReceived(());
+    assert!(status.has_pending_request() && !status.is_wait_reply_status());
+    status.cancel_wordle_request(false);
+    assert_eq!(status.kind(), SessionStatusKind::Init);
+    let mut status = SessionStatus::ReplyReceived(());
+    status.cancel_wordle_request(true);
+    assert_eq!(status.kind(), SessionStatusKind::WaitUserInput);
+    assert!(!status.has_pending_request());
+
+    let status = SessionStatus::<()>::GameOver(GameStatus::GuessTimeout);
+    assert!(!status.is_active() && !status.is_wait_reply_status());
+    assert_eq!(
+        SessionStatus::<()>::default().kind(),
+        SessionStatusKind::Init
+    );
+}
@@ -1,5 +1,3 @@
-use wordle_io::WordleMetadata;
-
 fn main() {
-    gear_wasm_builder::build_with_metadata::<WordleMetadata>();
+    gear_wasm_builder::build();
 }
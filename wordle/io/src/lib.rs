@@ -1,33 +1,257 @@
 #![no_std]
 
-use gmeta::{InOut, Metadata};
+use blake2::{digest::consts::U32, Blake2b, Digest};
 use gstd::{prelude::*, ActorId};
+use wordle_core::WordError;
 
-pub struct WordleMetadata;
+pub use wordle_core::{feedback_positions, score_word, Alphabet, LetterFeedback};
 
-impl Metadata for WordleMetadata {
-    type Init = ();
-    type Handle = InOut<Action, Event>;
-    type Others = ();
-    type Reply = ();
-    type Signal = ();
-    type State = ();
+/// Public view of the program. Secret words and their salts are never exposed.
+#[derive(Debug, Default, Clone, Encode, Decode, TypeInfo)]
+pub struct WordleState {
+    pub owner: ActorId,
+    pub authorized_callers: Vec<ActorId>,
+    pub daily_word_length: u8,
+    pub alphabet: Alphabet,
+    /// `(word_length, number_of_words)` for every length that has a bank.
+    pub bank_sizes: Vec<(u8, u32)>,
+    pub dictionary_size: u32,
+    pub active_games: u32,
+    /// Games removed from storage by [`Action::EndGame`]. Together with
+    /// `active_games` it shows whether finished games are being cleaned up.
+    pub ended_games: u64,
+    pub games: Vec<GameInfo>,
+    pub daily_period_blocks: u32,
+    pub current_period: u32,
+    /// The current daily challenge and the one before it, if they were played.
+    pub daily: Vec<DailyInfo>,
+}
+
+#[derive(Debug, Default, Clone, Encode, Decode, TypeInfo)]
+pub struct DailyInfo {
+    pub period: u32,
+    pub commitment: [u8; 32],
+    pub players: u32,
+    /// The word and salt, published once the period is over.
+    pub revealed: Option<(String, [u8; 32])>,
+}
+
+#[derive(Debug, Default, Clone, Encode, Decode, TypeInfo)]
+pub struct GameInfo {
+    pub user: ActorId,
+    pub game_id: GameId,
+    pub guesses: u32,
+    pub commitment: [u8; 32],
+}
+
+/// Identifies one of the games of a user. Ids are chosen by the caller when the
+/// game starts, so a user can play several games at once.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Encode, Decode, TypeInfo,
+)]
+pub struct GameId(pub u64);
+
+#[derive(Debug, Default, Clone, Encode, Decode, TypeInfo)]
+pub struct WordleInit {
+    /// Words of any length from [`wordle_core::MIN_WORD_LENGTH`] to
+    /// [`wordle_core::MAX_WORD_LENGTH`]; each goes to the bank of its length.
+    pub words: Vec<String>,
+    /// Length of the daily challenge words. The bank of this length can't be
+    /// emptied.
+    pub daily_word_length: u8,
+    /// Letters the words of the bank and the guesses are made of.
+    pub alphabet: Alphabet,
+    /// Length of a daily challenge period. Period `n` covers the blocks from
+    /// `n * daily_period_blocks` up to the start of period `n + 1`.
+    pub daily_period_blocks: u32,
+    /// Programs allowed to run games, see [`Action::AuthorizeCaller`].
+    pub authorized_callers: Vec<ActorId>,
+}
+
+impl WordleInit {
+    pub fn assert_valid(&self) {
+        assert!(
+            wordle_core::is_supported_length(self.daily_word_length),
+            "Invalid daily_word_length"
+        );
+        assert!(!self.alphabet.is_empty(), "Invalid alphabet");
+        assert!(self.daily_period_blocks > 0, "Invalid daily_period_blocks");
+        let mut has_daily_words = false;
+        for word in &self.words {
+            let word_length = checked_word_length(word, &self.alphabet)
+                .unwrap_or_else(|_| panic!("Invalid word in the bank: {word}"));
+            has_daily_words |= word_length == self.daily_word_length;
+        }
+        assert!(
+            has_daily_words,
+            "The bank of daily_word_length words must not be empty"
+        );
+    }
+}
+
+/// Checks `word` against the rules of [`wordle_core::validate_word`].
+pub fn validate_word(word: &str, word_length: u8, alphabet: &Alphabet) -> Result<(), WordleError> {
+    wordle_core::validate_word(word, word_length, alphabet).map_err(Into::into)
+}
+
+/// Checks a word of any supported length with
+/// [`wordle_core::checked_word_length`] and returns its length.
+pub fn checked_word_length(word: &str, alphabet: &Alphabet) -> Result<u8, WordleError> {
+    wordle_core::checked_word_length(word, alphabet).map_err(Into::into)
 }
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo)]
 pub enum Action {
-    StartGame { user: ActorId },
-    CheckWord { user: ActorId, word: String },
+    /// Starts a game on a random word of the bank of `word_length`.
+    StartGame {
+        user: ActorId,
+        game_id: GameId,
+        word_length: u8,
+    },
+    CheckWord {
+        user: ActorId,
+        game_id: GameId,
+        word: String,
+    },
+    /// Finishes the user's game, removes it from storage and reveals the
+    /// committed word.
+    EndGame {
+        user: ActorId,
+        game_id: GameId,
+    },
+    /// Starts a game on the word of the current period. Every player gets the
+    /// same word, of `daily_word_length` letters, and may start only one daily
+    /// game per period.
+    StartDailyGame {
+        user: ActorId,
+        game_id: GameId,
+    },
+    AddWords(Vec<String>),
+    RemoveWords(Vec<String>),
+    /// Appends a chunk of allowed guesses to the dictionary. Large dictionaries
+    /// are uploaded with several of these messages to stay under the message
+    /// size limit.
+    ExtendDictionary(Vec<String>),
+    /// Same as [`Action::CheckWord`], but answered with the index lists of
+    /// [`Event::WordCheckedV1`] for callers that still expect them.
+    CheckWordV1 {
+        user: ActorId,
+        game_id: GameId,
+        word: String,
+    },
+    /// Allows `ActorId` to start, play and end games on behalf of users.
+    /// Game actions from any other sender are rejected with
+    /// [`WordleError::NotAuthorized`], since Wordle trusts the `user` field
+    /// of these actions.
+    AuthorizeCaller(ActorId),
+    RevokeCaller(ActorId),
 }
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo)]
 pub enum Event {
+    /// `commitment` is [`commit_word`] over the secret word and a random salt,
+    /// both of which are published in [`Event::GameOver`]. `word_length` is
+    /// the number of letters of the secret word; for a daily game it is the
+    /// `daily_word_length` of this program. Guesses may only use the letters
+    /// of `alphabet`.
     GameStarted {
         user: ActorId,
+        game_id: GameId,
+        commitment: [u8; 32],
+        word_length: u8,
+        alphabet: Alphabet,
     },
+    /// `feedback` has one entry per letter of the guess.
     WordChecked {
         user: ActorId,
+        game_id: GameId,
+        feedback: Vec<LetterFeedback>,
+    },
+    /// The guess is neither in the bank of words nor in the dictionary of
+    /// allowed guesses, so it was not scored.
+    InvalidWord {
+        user: ActorId,
+        game_id: GameId,
+        word: String,
+    },
+    GameOver {
+        user: ActorId,
+        game_id: GameId,
+        word: String,
+        salt: [u8; 32],
+    },
+    Error(WordleError),
+    /// A daily game is over while its period is still running. The word is
+    /// revealed in the state once block `reveal_at` is reached.
+    GameEnded {
+        user: ActorId,
+        game_id: GameId,
+        reveal_at: u32,
+    },
+    WordsAdded {
+        count: u32,
+    },
+    WordsRemoved {
+        count: u32,
+    },
+    DictionaryExtended {
+        count: u32,
+    },
+    /// The reply to [`Action::CheckWordV1`], see [`feedback_positions`].
+    WordCheckedV1 {
+        user: ActorId,
+        game_id: GameId,
         correct_positions: Vec<u8>,
         contained_in_word: Vec<u8>,
     },
+    CallerAuthorized(ActorId),
+    CallerRevoked(ActorId),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum WordleError {
+    NotInGame,
+    InvalidWordLength,
+    InvalidCharacters,
+    /// The action is reserved for the owner of the program.
+    NotOwner,
+    /// Removing the words would leave the bank of daily challenge words empty.
+    EmptyBank,
+    /// The user already has a game with this id.
+    GameAlreadyStarted,
+    /// The user already played the daily challenge of this period.
+    AlreadyPlayed,
+    /// The period of the daily game is over and its word has been revealed.
+    ChallengeEnded,
+    /// Game actions are only accepted from authorized callers.
+    NotAuthorized,
+    /// There is no bank of words of the requested length.
+    UnsupportedWordLength,
+}
+
+impl From<WordError> for WordleError {
+    fn from(error: WordError) -> Self {
+        match error {
+            WordError::InvalidWordLength => WordleError::InvalidWordLength,
+            WordError::InvalidCharacters => WordleError::InvalidCharacters,
+        }
+    }
+}
+
+/// Commits to `word` as `blake2b-256(salt ++ word)`.
+///
+/// The commitment is published when the game starts; once the word and salt
+/// are revealed anyone can recompute it to check that the word never changed.
+///
+/// The commitment only proves that the word was fixed at the start; it does
+/// not keep the word secret. The word and the salt are drawn with
+/// `exec::random`, which mixes the id of the start message with the
+/// randomness seed of the block. Both are public once the block is produced,
+/// so anyone who reads the chain can recompute the draws, and a validator
+/// can bias them. Don't put anything of value on a game's outcome.
+pub fn commit_word(word: &str, salt: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Blake2b::<U32>::new();
+    hasher.update(salt);
+    hasher.update(word.as_bytes());
+    hasher.finalize().into()
 }
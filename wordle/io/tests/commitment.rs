@@ -0,0 +1,13 @@
+use wordle_io::commit_word;
+
+#[test]
+fn test_commit_word() {
+    let salt = [7; 32];
+    let commitment = commit_word("horse", &salt);
+
+    // The same word and salt always give the same commitment
+    assert_eq!(commitment, commit_word("horse", &salt));
+    // Changing either the word or the salt changes it
+    assert_ne!(commitment, commit_word("house", &salt));
+    assert_ne!(commitment, commit_word("horse", &[8; 32]));
+}
@@ -1,74 +1,461 @@
 #![no_std]
 
-use gstd::{collections::HashMap, exec, msg, prelude::*, ActorId};
+use gstd::{
+    collections::{BTreeMap, HashMap, HashSet},
+    exec, msg,
+    prelude::*,
+    ActorId,
+};
 use wordle_io::*;
 
 static mut WORDLE: Option<Wordle> = None;
 
-const BANK_OF_WORDS: [&str; 3] = ["house", "human", "horse"];
-
 #[derive(Default)]
 struct Wordle {
-    games: HashMap<ActorId, String>,
+    owner: ActorId,
+    daily_word_length: u8,
+    alphabet: Alphabet,
+    /// One bank of words per word length, a length without words has no entry.
+    banks: BTreeMap<u8, Vec<String>>,
+    dictionary: HashSet<String>,
+    games: HashMap<(ActorId, GameId), Game>,
+    ended_games: u64,
+    authorized_callers: HashSet<ActorId>,
+    daily_period_blocks: u32,
+    daily: Option<DailyChallenge>,
+    previous_daily: Option<DailyChallenge>,
+}
+
+struct Game {
+    word: String,
+    salt: [u8; 32],
+    guesses: u32,
+    /// Period of the daily challenge, `None` for a game on a random word.
+    daily_period: Option<u32>,
+}
+
+struct DailyChallenge {
+    period: u32,
+    word: String,
+    salt: [u8; 32],
+    players: HashSet<ActorId>,
+}
+
+impl DailyChallenge {
+    fn info(&self, current_period: u32) -> DailyInfo {
+        DailyInfo {
+            period: self.period,
+            commitment: commit_word(&self.word, &self.salt),
+            players: self.players.len() as u32,
+            revealed: (self.period < current_period).then(|| (self.word.clone(), self.salt)),
+        }
+    }
+}
+
+impl Wordle {
+    fn current_period(&self) -> u32 {
+        exec::block_height() / self.daily_period_blocks
+    }
+
+    /// Picks a word of the bank of `word_length` letters. `random` has to be
+    /// drawn independently of the salt, or revealing one reveals the other.
+    fn random_word(&self, word_length: u8, random: &[u8; 32]) -> Result<String, WordleError> {
+        let bank = self
+            .banks
+            .get(&word_length)
+            .ok_or(WordleError::UnsupportedWordLength)?;
+        let mut index = [0; 8];
+        index.copy_from_slice(&random[..8]);
+        let random_id = u64::from_le_bytes(index) % bank.len() as u64;
+        Ok(bank[random_id as usize].clone())
+    }
+
+    fn is_allowed_guess(&self, word: &str) -> bool {
+        self.dictionary.contains(word)
+            || self
+                .banks
+                .get(&(word.chars().count() as u8))
+                .is_some_and(|bank| bank.iter().any(|w| w == word))
+    }
+
+    fn assert_owner(&self) -> Result<(), WordleError> {
+        if msg::source() != self.owner {
+            return Err(WordleError::NotOwner);
+        }
+        Ok(())
+    }
+
+    fn assert_authorized(&self) -> Result<(), WordleError> {
+        if !self.authorized_callers.contains(&msg::source()) {
+            return Err(WordleError::NotAuthorized);
+        }
+        Ok(())
+    }
+
+    fn start_game(
+        &mut self,
+        user: ActorId,
+        game_id: GameId,
+        word_length: u8,
+    ) -> Result<Event, WordleError> {
+        self.assert_authorized()?;
+        if self.games.contains_key(&(user, game_id)) {
+            return Err(WordleError::GameAlreadyStarted);
+        }
+        let word = self.random_word(word_length, &random_bytes(Draw::Word))?;
+        let salt = random_bytes(Draw::Salt);
+        let commitment = commit_word(&word, &salt);
+        self.games.insert(
+            (user, game_id),
+            Game {
+                word,
+                salt,
+                guesses: 0,
+                daily_period: None,
+            },
+        );
+        Ok(Event::GameStarted {
+            user,
+            game_id,
+            commitment,
+            word_length,
+            alphabet: self.alphabet.clone(),
+        })
+    }
+
+    /// The word of a period is drawn by the first daily game of that period and
+    /// kept until the next period starts.
+    fn start_daily_game(&mut self, user: ActorId, game_id: GameId) -> Result<Event, WordleError> {
+        self.assert_authorized()?;
+        if self.games.contains_key(&(user, game_id)) {
+            return Err(WordleError::GameAlreadyStarted);
+        }
+        let period = self.current_period();
+        if self.daily.as_ref().map(|daily| daily.period) != Some(period) {
+            let daily = DailyChallenge {
+                period,
+                word: self.random_word(self.daily_word_length, &random_bytes(Draw::Word))?,
+                salt: random_bytes(Draw::Salt),
+                players: HashSet::new(),
+            };
+            self.previous_daily = self.daily.replace(daily);
+        }
+
+        let daily = self
+            .daily
+            .as_mut()
+            .expect("The daily challenge is not drawn");
+        if !daily.players.insert(user) {
+            return Err(WordleError::AlreadyPlayed);
+        }
+        self.games.insert(
+            (user, game_id),
+            Game {
+                word: daily.word.clone(),
+                salt: daily.salt,
+                guesses: 0,
+                daily_period: Some(period),
+            },
+        );
+        Ok(Event::GameStarted {
+            user,
+            game_id,
+            commitment: commit_word(&daily.word, &daily.salt),
+            word_length: self.daily_word_length,
+            alphabet: self.alphabet.clone(),
+        })
+    }
+
+    fn check_word(
+        &mut self,
+        user: ActorId,
+        game_id: GameId,
+        word: String,
+    ) -> Result<Event, WordleError> {
+        self.assert_authorized()?;
+        // The guess must have as many letters as the word of this game
+        let word_length = self
+            .games
+            .get(&(user, game_id))
+            .ok_or(WordleError::NotInGame)?
+            .word
+            .chars()
+            .count() as u8;
+        validate_word(&word, word_length, &self.alphabet)?;
+        let is_allowed_guess = self.is_allowed_guess(&word);
+        let current_period = self.current_period();
+        let game = self
+            .games
+            .get_mut(&(user, game_id))
+            .ok_or(WordleError::NotInGame)?;
+        if game
+            .daily_period
+            .is_some_and(|period| period != current_period)
+        {
+            return Err(WordleError::ChallengeEnded);
+        }
+        if !is_allowed_guess {
+            return Ok(Event::InvalidWord {
+                user,
+                game_id,
+                word,
+            });
+        }
+
+        game.guesses += 1;
+        Ok(Event::WordChecked {
+            user,
+            game_id,
+            feedback: score_word(&game.word, &word),
+        })
+    }
+
+    fn check_word_v1(
+        &mut self,
+        user: ActorId,
+        game_id: GameId,
+        word: String,
+    ) -> Result<Event, WordleError> {
+        let event = match self.check_word(user, game_id, word)? {
+            Event::WordChecked {
+                user,
+                game_id,
+                feedback,
+            } => {
+                let (correct_positions, contained_in_word) = feedback_positions(&feedback);
+                Event::WordCheckedV1 {
+                    user,
+                    game_id,
+                    correct_positions,
+                    contained_in_word,
+                }
+            }
+            event => event,
+        };
+        Ok(event)
+    }
+
+    /// The word of a daily game stays secret until its period is over.
+    fn end_game(&mut self, user: ActorId, game_id: GameId) -> Result<Event, WordleError> {
+        self.assert_authorized()?;
+        let current_period = self.current_period();
+        let Game {
+            word,
+            salt,
+            daily_period,
+            ..
+        } = self
+            .games
+            .remove(&(user, game_id))
+            .ok_or(WordleError::NotInGame)?;
+        self.ended_games += 1;
+        match daily_period {
+            Some(period) if period == current_period => Ok(Event::GameEnded {
+                user,
+                game_id,
+                reveal_at: (period + 1) * self.daily_period_blocks,
+            }),
+            _ => Ok(Event::GameOver {
+                user,
+                game_id,
+                word,
+                salt,
+            }),
+        }
+    }
+
+    fn authorize_caller(&mut self, caller: ActorId) -> Result<Event, WordleError> {
+        self.assert_owner()?;
+        self.authorized_callers.insert(caller);
+        Ok(Event::CallerAuthorized(caller))
+    }
+
+    fn revoke_caller(&mut self, caller: ActorId) -> Result<Event, WordleError> {
+        self.assert_owner()?;
+        self.authorized_callers.remove(&caller);
+        Ok(Event::CallerRevoked(caller))
+    }
+
+    /// Each word goes to the bank of its length, which is created if needed.
+    fn add_words(&mut self, words: Vec<String>) -> Result<Event, WordleError> {
+        self.assert_owner()?;
+        let mut lengths = Vec::with_capacity(words.len());
+        for word in &words {
+            lengths.push(checked_word_length(word, &self.alphabet)?);
+        }
+
+        let mut count = 0;
+        for (word, word_length) in words.into_iter().zip(lengths) {
+            let bank = self.banks.entry(word_length).or_default();
+            if !bank.contains(&word) {
+                bank.push(word);
+                count += 1;
+            }
+        }
+        Ok(Event::WordsAdded { count })
+    }
+
+    /// Banks other than the one of the daily challenge may be emptied, which
+    /// stops new games of their length.
+    fn remove_words(&mut self, words: Vec<String>) -> Result<Event, WordleError> {
+        self.assert_owner()?;
+        if self
+            .banks
+            .get(&self.daily_word_length)
+            .is_none_or(|bank| bank.iter().all(|word| words.contains(word)))
+        {
+            return Err(WordleError::EmptyBank);
+        }
+
+        let mut count = 0;
+        for bank in self.banks.values_mut() {
+            let size_before = bank.len();
+            bank.retain(|word| !words.contains(word));
+            count += (size_before - bank.len()) as u32;
+        }
+        self.banks.retain(|_, bank| !bank.is_empty());
+        Ok(Event::WordsRemoved { count })
+    }
+
+    fn extend_dictionary(&mut self, words: Vec<String>) -> Result<Event, WordleError> {
+        self.assert_owner()?;
+        for word in &words {
+            checked_word_length(word, &self.alphabet)?;
+        }
+
+        let mut count = 0;
+        for word in words {
+            if self.dictionary.insert(word) {
+                count += 1;
+            }
+        }
+        Ok(Event::DictionaryExtended { count })
+    }
 }
 
 #[no_mangle]
 extern "C" fn init() {
+    let wordle_init: WordleInit = msg::load().expect("Unable to decode WordleInit");
+    wordle_init.assert_valid();
+
+    let mut banks: BTreeMap<u8, Vec<String>> = BTreeMap::new();
+    for word in wordle_init.words {
+        let bank = banks.entry(word.chars().count() as u8).or_default();
+        if !bank.contains(&word) {
+            bank.push(word);
+        }
+    }
+
     unsafe {
         WORDLE = Some(Wordle {
+            owner: msg::source(),
+            daily_word_length: wordle_init.daily_word_length,
+            alphabet: wordle_init.alphabet,
+            banks,
+            dictionary: HashSet::new(),
             games: HashMap::new(),
+            ended_games: 0,
+            authorized_callers: wordle_init.authorized_callers.into_iter().collect(),
+            daily_period_blocks: wordle_init.daily_period_blocks,
+            daily: None,
+            previous_daily: None,
         });
     }
 }
 
 #[no_mangle]
+#[allow(static_mut_refs)]
 extern "C" fn handle() {
     let action: Action = msg::load().expect("Unable to decode action");
     let wordle = unsafe { WORDLE.as_mut().expect("The program is not initialized") };
 
     let reply = match action {
-        Action::StartGame { user } => {
-            let random_id = get_random_value(BANK_OF_WORDS.len() as u8);
-            let word = BANK_OF_WORDS[random_id as usize];
-            wordle.games.insert(user, word.to_string());
-            Event::GameStarted { user }
-        }
-        Action::CheckWord { user, word } => {
-            if word.len() != 5 {
-                panic!("The length of the word must be 5 characters");
-            }
-            let key_word = wordle
-                .games
-                .get(&user)
-                .expect("There is no game with this user");
-            let mut matched_indices = Vec::with_capacity(5);
-            let mut key_indices = Vec::with_capacity(5);
-            for (i, (a, b)) in key_word.chars().zip(word.chars()).enumerate() {
-                if a == b {
-                    matched_indices.push(i as u8);
-                } else if key_word.contains(b) {
-                    key_indices.push(i as u8);
-                }
-            }
-
-            Event::WordChecked {
-                user,
-                correct_positions: matched_indices,
-                contained_in_word: key_indices,
-            }
-        }
-    };
+        Action::StartGame {
+            user,
+            game_id,
+            word_length,
+        } => wordle.start_game(user, game_id, word_length),
+        Action::CheckWord {
+            user,
+            game_id,
+            word,
+        } => wordle.check_word(user, game_id, word),
+        Action::EndGame { user, game_id } => wordle.end_game(user, game_id),
+        Action::StartDailyGame { user, game_id } => wordle.start_daily_game(user, game_id),
+        Action::AddWords(words) => wordle.add_words(words),
+        Action::RemoveWords(words) => wordle.remove_words(words),
+        Action::ExtendDictionary(words) => wordle.extend_dictionary(words),
+        Action::CheckWordV1 {
+            user,
+            game_id,
+            word,
+        } => wordle.check_word_v1(user, game_id, word),
+        Action::AuthorizeCaller(caller) => wordle.authorize_caller(caller),
+        Action::RevokeCaller(caller) => wordle.revoke_caller(caller),
+    }
+    .unwrap_or_else(Event::Error);
 
     msg::reply(reply, 0).expect("Error in sending a reply");
 }
 
-static mut SEED: u8 = 0;
+#[no_mangle]
+#[allow(static_mut_refs)]
+extern "C" fn state() {
+    let wordle = unsafe { WORDLE.as_ref().expect("The program is not initialized") };
+    msg::reply::<WordleState>(wordle.into(), 0).expect("Failed to share state");
+}
+
+impl From<&Wordle> for WordleState {
+    fn from(wordle: &Wordle) -> Self {
+        let current_period = wordle.current_period();
+        Self {
+            owner: wordle.owner,
+            authorized_callers: wordle.authorized_callers.iter().copied().collect(),
+            daily_word_length: wordle.daily_word_length,
+            alphabet: wordle.alphabet.clone(),
+            bank_sizes: wordle
+                .banks
+                .iter()
+                .map(|(word_length, bank)| (*word_length, bank.len() as u32))
+                .collect(),
+            dictionary_size: wordle.dictionary.len() as u32,
+            active_games: wordle.games.len() as u32,
+            ended_games: wordle.ended_games,
+            games: wordle
+                .games
+                .iter()
+                .map(|((user, game_id), game)| GameInfo {
+                    user: *user,
+                    game_id: *game_id,
+                    guesses: game.guesses,
+                    commitment: commit_word(&game.word, &game.salt),
+                })
+                .collect(),
+            daily_period_blocks: wordle.daily_period_blocks,
+            current_period,
+            daily: wordle
+                .previous_daily
+                .iter()
+                .chain(&wordle.daily)
+                .map(|daily| daily.info(current_period))
+                .collect(),
+        }
+    }
+}
+
+/// What a random draw is used for.
+#[derive(Clone, Copy)]
+enum Draw {
+    Word = 1,
+    Salt = 2,
+}
 
-pub fn get_random_value(range: u8) -> u8 {
-    let seed = unsafe { SEED };
-    unsafe { SEED = SEED.wrapping_add(1) };
-    let mut random_input: [u8; 32] = exec::program_id().into();
-    random_input[0] = random_input[0].wrapping_add(seed);
-    let (random, _) = exec::random(random_input).expect("Error in getting random number");
-    random[0] % range
+/// Draws 32 random bytes. The subject is the id of the current message, so
+/// that every `StartGame` gets its own values, tagged with `draw` so that the
+/// word and the salt of a game come from independent draws.
+fn random_bytes(draw: Draw) -> [u8; 32] {
+    let mut subject: [u8; 32] = msg::id().into();
+    subject[0] ^= draw as u8;
+    let (random, _) = exec::random(subject).expect("Error in getting random number");
+    random
 }